@@ -4,6 +4,8 @@ use conrod::{self, Positionable, Sizeable, Widget, UiCell};
 use conrod::event::Drag;
 use error::BarError;
 use image;
+use layout::{self, Layout, Slot};
+use message::{Screenshot, ScreenshotResult};
 use self::glium::glutin::Event::KeyboardInput;
 use self::glium::glutin::VirtualKeyCode as KeyCode;
 use self::glium::{DisplayBuild, Surface};
@@ -20,74 +22,19 @@ use widgets::sep::Sep;
 // https://gist.github.com/anonymous/d1b3dfbabe5dac2995da37da41d18625
 
 // TODO Height should be detected by font height
-pub const DEFAULT_GAUGE_WIDTH: u32 = 200;
+pub const DEFAULT_GAUGE_WIDTH: u32 = layout::DEFAULT_WIDTH;
 pub const DEFAULT_SEP_WIDTH: u32 = 24;
 
 struct Binder {
     bind: Box<Fn(Id, &mut UiCell, UpdateConfig) -> UpdateConfig> ,
+    // width of what was drawn last frame, everything in the slot if None.
+    measure: Option<Box<Fn(&conrod::Ui) -> Option<u32>>>,
     id: Id,
     width: u32,
-    layout: super::Layout,
+    layout: Layout,
     update: UpdateConfig,
 }
 
-trait BinderList {
-    fn update_widths(&mut self, &conrod::Ui);
-    fn total_width(&self) -> u32;
-}
-
-impl BinderList for Vec<Binder> {
-
-    fn total_width(&self) -> u32 {
-        self.iter().fold(0, |sum, c| sum + c.width)
-    }
-
-    fn update_widths(&mut self, ui: &conrod::Ui) {
-        for b in self.iter_mut() {
-            let &mut Binder{width: prevw, layout, id, ..} = b;
-
-            let mut w = match layout.width {
-                Some(w) => w,
-                None => {
-
-                    // if a width isn't set get the width from node contents
-                    let bbox = ui.kids_bounding_box(id);
-
-                    // TODO also consider effects of padding & margins?
-                    match bbox.map(|rect| rect.x.len()) {
-                        Some(w) => w as u32,
-                        None => DEFAULT_GAUGE_WIDTH,
-                    }
-                }
-            };
-
-            if let Some(min_w) = layout.minwidth {
-                if w < min_w {
-                    w = min_w;
-                }
-            }
-
-            if let Some(max_w) = layout.maxwidth {
-                if w > max_w {
-                    w = max_w;
-                }
-            }
-
-            // If the difference between values is less than or equal to the
-            // smoothing delta keep the greater of the values.
-            if let Some(dw) = layout.smoothwidth {
-                let diff = w as i32 - prevw as i32;
-                if diff.abs() as u32 <= dw {
-                    w = std::cmp::max(w, prevw);
-                }
-            }
-
-            // set the new width;
-            b.width = w;
-        }
-    }
-}
-
 pub struct Bar {}
 
 impl Bar {
@@ -129,18 +76,58 @@ impl Bar {
 
         ui_txs
     }
+
+    // Renders to an offscreen software GL context instead of a window.
+    // Each frame conrod draws comes back as an RGBA image. Nothing happens
+    // until the first `DispResponse::WakeDisplay`, as there are no window
+    // events to start things off.
+    pub fn run_headless<F, T>(&self,
+                              width: u32,
+                              height: u32,
+                              app_tx: mpsc::Sender<T>,
+                              ui_renderer: Arc<F>)
+                              -> (Vec<mpsc::Sender<DispResponse>>, mpsc::Receiver<image::RgbaImage>)
+        where F: Fn(&mut UiLoop, mpsc::Sender<T>) + 'static + Sync + Send,
+              T: 'static + Sync + Send
+    {
+        let (ui_tx, ui_rx) = mpsc::channel();
+        let (disp_tx, disp_rx) = mpsc::channel();
+        let (frame_tx, frame_rx) = mpsc::channel();
+
+        {
+            let ui_tx = ui_tx.clone();
+            std::thread::spawn(move || {
+                DisplayLoop::run_headless(width, height, ui_tx, disp_rx, frame_tx);
+            });
+        }
+        std::thread::spawn(move || {
+            UiLoop::run(ui_renderer, "headless".to_owned(), ui_rx, disp_tx, app_tx);
+        });
+
+        (vec![ui_tx], frame_rx)
+    }
 }
 
 pub struct DisplayInfo {
-    proxy: glium::glutin::WindowProxy,
+    // None when headless, there's no event loop to wake.
+    proxy: Option<glium::glutin::WindowProxy>,
     width: u32,
     height: u32,
 }
 
+impl DisplayInfo {
+    fn wakeup(&self) {
+        if let Some(ref proxy) = self.proxy {
+            proxy.wakeup_event_loop();
+        }
+    }
+}
+
 pub enum UiRequest {
     DisplayInfo,
     ImageId(PathBuf),
     Primitives(conrod::render::OwnedPrimitives),
+    Screenshot(PathBuf, mpsc::Sender<ScreenshotResult>),
 }
 
 pub enum DispResponse {
@@ -148,6 +135,7 @@ pub enum DispResponse {
     Event(conrod::event::Input),
     ImageId(conrod::image::Id),
     WakeDisplay,
+    Screenshot(Screenshot, mpsc::Sender<ScreenshotResult>),
 }
 
 
@@ -190,6 +178,31 @@ impl DisplayLoop {
         dloop.process_events(tx, rx);
     }
 
+    // glutin's headless renderer uses OSMesa so this needs no X server or
+    // GPU, only libOSMesa.
+    fn run_headless(width: u32,
+                    height: u32,
+                    tx: mpsc::Sender<DispResponse>,
+                    rx: mpsc::Receiver<UiRequest>,
+                    frames: mpsc::Sender<image::RgbaImage>) {
+
+        let display = match glium::glutin::HeadlessRendererBuilder::new(width, height)
+            .build_glium() {
+                Ok(display) => display,
+                Err(e) => {
+                    println!("headless display ERROR: {}", e);
+                    return;
+                }
+            };
+
+        let dloop = &mut DisplayLoop{
+            display: display,
+            image_map: conrod::image::Map::new(),
+        };
+
+        dloop.process_requests(tx, rx, frames);
+    }
+
     pub fn load_image<P>(&mut self, path: P) -> conrod::image::Id
         where P: AsRef<Path>
     {
@@ -207,7 +220,7 @@ impl DisplayLoop {
 
     fn display_info(&self) -> DisplayInfo {
         let (width, height) = self.window_dims();
-        let proxy =  self.display.get_window().unwrap().create_window_proxy();
+        let proxy = self.display.get_window().map(|w| w.create_window_proxy());
 
         DisplayInfo{
             proxy: proxy,
@@ -224,6 +237,7 @@ impl DisplayLoop {
         let mut renderer = conrod::backend::glium::Renderer::new(
             &self.display
         ).unwrap();
+        let mut drawn = false;
 
         'main: loop {
 
@@ -268,6 +282,7 @@ impl DisplayLoop {
             // obtained at least one primitive to render.
             // Only draw the last primitive from the queue (ignore the others).
             let mut maybe_primitives = None;
+            let mut screenshots = Vec::new();
             while let Ok(resp) = rx.try_recv() {
                 match resp {
                     UiRequest::Primitives(next_primitives) => {
@@ -283,6 +298,8 @@ impl DisplayLoop {
                         let id = self.load_image(path);
                         tx.send(DispResponse::ImageId(id)).unwrap();
                     },
+
+                    UiRequest::Screenshot(path, result) => screenshots.push((path, result)),
                 }
             }
 
@@ -295,6 +312,17 @@ impl DisplayLoop {
                 renderer.draw(&self.display, &mut target, &self.image_map).unwrap();
 
                 target.finish().unwrap();
+                drawn = true;
+            }
+
+            // after drawing so they get the latest frame.
+            for (path, result) in screenshots {
+                let saved = if drawn {
+                    self.save_frame(&renderer, &path)
+                } else {
+                    Err(BarError::Bar("nothing drawn yet".to_owned()))
+                };
+                result.send(Some(saved.map_err(|e| format!("{}: {}", path.display(), e)))).ok();
             }
 
             last_update = std::time::Instant::now();
@@ -302,6 +330,79 @@ impl DisplayLoop {
     }
 
 
+    // Like `process_events` but drawing into a texture which is read back
+    // and sent on `frames`. Runs until the ui thread goes away.
+    fn process_requests(&mut self,
+                        tx: mpsc::Sender<DispResponse>,
+                        rx: mpsc::Receiver<UiRequest>,
+                        frames: mpsc::Sender<image::RgbaImage>) {
+
+        let mut renderer = conrod::backend::glium::Renderer::new(
+            &self.display
+        ).unwrap();
+
+        let (width, height) = self.window_dims();
+        let texture = glium::texture::Texture2d::empty(
+            &self.display, width, height
+        ).unwrap();
+
+        while let Ok(req) = rx.recv() {
+            match req {
+                UiRequest::Primitives(primitives) => {
+                    renderer.fill(&self.display, primitives.walk(), &self.image_map);
+
+                    let mut target = glium::framebuffer::SimpleFrameBuffer::new(
+                        &self.display, &texture
+                    ).unwrap();
+                    target.clear_color(0.0, 0.0, 0.0, 1.0);
+
+                    renderer.draw(&self.display, &mut target, &self.image_map).unwrap();
+
+                    let raw: glium::texture::RawImage2d<u8> = texture.read();
+                    if frames.send(to_image(raw)).is_err() {
+                        break;
+                    }
+                },
+
+                UiRequest::DisplayInfo => {
+                    let info_resp = self.display_info();
+                    tx.send(DispResponse::DisplayInfo(info_resp)).unwrap();
+                },
+
+                UiRequest::ImageId(path) => {
+                    let id = self.load_image(path);
+                    tx.send(DispResponse::ImageId(id)).unwrap();
+                },
+
+                UiRequest::Screenshot(path, result) => {
+                    let raw: glium::texture::RawImage2d<u8> = texture.read();
+                    let saved = save_png(raw, &path);
+                    result.send(Some(saved.map_err(|e| format!("{}: {}", path.display(), e)))).ok();
+                },
+            }
+        }
+    }
+
+    // Draws the frame the renderer was last filled with into a texture and
+    // saves that. Reading the window back would catch whatever covers it.
+    fn save_frame(&self,
+                  renderer: &conrod::backend::glium::Renderer,
+                  path: &Path) -> Result<(), BarError> {
+        let (width, height) = self.display.get_framebuffer_dimensions();
+        let texture = glium::texture::Texture2d::empty(&self.display, width, height)
+            .map_err(|e| BarError::Bar(format!("{:?}", e)))?;
+
+        {
+            let mut target = glium::framebuffer::SimpleFrameBuffer::new(&self.display, &texture)
+                .map_err(|e| BarError::Bar(format!("{:?}", e)))?;
+            target.clear_color(0.0, 0.0, 0.0, 1.0);
+            renderer.draw(&self.display, &mut target, &self.image_map)
+                .map_err(|e| BarError::Bar(format!("{:?}", e)))?;
+        }
+
+        save_png(texture.read(), path)
+    }
+
     fn window_dims(&self) -> (u32, u32) {
         let window = self.display.get_window();
         if let Some(window) = window {
@@ -309,15 +410,33 @@ impl DisplayLoop {
                 return dims
             };
         }
-        (0, 0)
+
+        // headless contexts have a framebuffer but no window.
+        self.display.get_framebuffer_dimensions()
     }
 }
 
+// GL rows run bottom to top.
+fn to_image(raw: glium::texture::RawImage2d<u8>) -> image::RgbaImage {
+    let frame: image::RgbaImage = image::ImageBuffer::from_raw(
+        raw.width, raw.height, raw.data.into_owned()
+    ).unwrap();
+
+    image::imageops::flip_vertical(&frame)
+}
+
+fn save_png(raw: glium::texture::RawImage2d<u8>, path: &Path) -> Result<(), BarError> {
+    to_image(raw).save(path)?;
+    Ok(())
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct UpdateConfig {
     needs_update: bool,
     last_update: std::time::Instant,
     width_update: Option<f64>,
+    minwidth_update: Option<Option<u32>>,
+    separator: bool,
 }
 
 impl UpdateConfig {
@@ -326,6 +445,8 @@ impl UpdateConfig {
         UpdateConfig{
             needs_update: false,
             width_update: None,
+            minwidth_update: None,
+            separator: true,
             last_update: std::time::Instant::now(),
         }
     }
@@ -345,6 +466,19 @@ impl UpdateConfig {
         self
     }
 
+    // For widgets whose minimum width comes from what they show, like an
+    // i3bar block's min_width.
+    pub fn apply_minwidth(mut self, minwidth: Option<u32>) -> Self {
+        self.minwidth_update = Some(minwidth);
+        self
+    }
+
+    // Whether the separator on the widget's inner side is drawn.
+    pub fn apply_separator(mut self, separator: bool) -> Self {
+        self.separator = separator;
+        self
+    }
+
     fn updated(mut self) -> Self {
         self.last_update = std::time::Instant::now();
         self
@@ -411,7 +545,7 @@ impl UiLoop {
         self.tx.send(UiRequest::ImageId(p)).unwrap();
 
         // wake up display thread in case it is blocking
-        self.display_info.proxy.wakeup_event_loop();
+        self.display_info.wakeup();
 
         // continue to listen until we receive it
         while let Ok(resp) = self.rx.recv() {
@@ -426,13 +560,14 @@ impl UiLoop {
         return Err(BarError::Bar(format!("{}", "Some damn image id error")));
     }
 
-    pub fn bind<F>(&mut self, layout: super::Layout, bind: F) -> &Self
+    pub fn bind<F>(&mut self, layout: Layout, bind: F) -> &Self
         where F: 'static + Send + Fn(Id, &mut UiCell, UpdateConfig) -> UpdateConfig
     {
         let id = self.gen_id();
 
         self.binders.push(Binder{
             bind: Box::new(bind),
+            measure: None,
             id: id,
             width: 0,
             layout: layout,
@@ -442,6 +577,38 @@ impl UiLoop {
         self
     }
 
+    // Like `bind` but the slot is sized by `measure`, usually a gauge's,
+    // rather than by everything drawn in it.
+    pub fn bind_measured<F, M>(&mut self, layout: Layout, bind: F, measure: M) -> &Self
+        where F: 'static + Send + Fn(Id, &mut UiCell, UpdateConfig) -> UpdateConfig,
+              M: 'static + Send + Fn(&conrod::Ui) -> Option<u32>
+    {
+        self.bind(layout, bind);
+        if let Some(binder) = self.binders.last_mut() {
+            binder.measure = Some(Box::new(measure));
+        }
+
+        self
+    }
+
+    // Passes the screenshot on to the display thread if it's for this bar,
+    // which sends back whether it was saved.
+    fn screenshot(&self, shot: Screenshot, result: mpsc::Sender<ScreenshotResult>) {
+        if shot.output.as_ref().map_or(false, |o| *o != self.output) {
+            result.send(None).ok();
+            return;
+        }
+
+        let path = shot.path.replace("{output}", &self.output);
+        match self.tx.send(UiRequest::Screenshot(PathBuf::from(path), result)) {
+            Ok(()) => self.display_info.wakeup(),
+            Err(mpsc::SendError(UiRequest::Screenshot(_, result))) => {
+                result.send(Some(Err("bar window has gone".to_owned()))).ok();
+            },
+            Err(_) => (),
+        }
+    }
+
     fn make_sep(slot_id: Id, sep_id: Id) -> Binder {
         Binder{
             bind: Box::new(move |slot_id, mut ui_widgets, mut update| {
@@ -457,9 +624,10 @@ impl UiLoop {
 
                 update
             }),
+            measure: None,
             id: slot_id,
             width: 0,
-            layout: super::Layout::new().with_width(Some(DEFAULT_SEP_WIDTH)),
+            layout: Layout::new().with_width(Some(DEFAULT_SEP_WIDTH)),
             update: UpdateConfig::new(),
         }
     }
@@ -479,41 +647,31 @@ impl UiLoop {
 
         let master_id;
         let spacer_id;
+        let spacer_i;
+        let slots;
         let mut binders = Vec::new();
-        let mut left_i = 0;
         {
             let mut gid = self.ui.widget_id_generator();
 
             master_id = gid.next();
             spacer_id = gid.next();
 
-            // insert a seperator between each widget and sort widgets so that
-            // Lefts grow inward and Rights grow inward. There are no seperators
-            // on the ends and there is a double seperator in the middle where the
-            // spacer will be inserted. variable left_i && spacer_i points here
-            // [l1 s l2 s s r2 s r1]
-            for b in self.binders {
-                match b.layout.orientation {
-                    super::Orientation::Left => {
-                        binders.insert(left_i, b);
-                        binders.insert(
-                            left_i + 1,
-                            UiLoop::make_sep(gid.next(), gid.next()),
-                        );
-                        left_i += 2;
-                    },
-                    super::Orientation::Right => {
-                        binders.insert(
-                            left_i,
-                            UiLoop::make_sep(gid.next(), gid.next()),
-                        );
-                        binders.insert(left_i + 1, b);
-                    },
-                }
+            let orientations: Vec<_> = self.binders.iter()
+                .map(|b| b.layout.orientation())
+                .collect();
+            let arrangement = layout::arrange(&orientations);
+
+            let mut widgets: Vec<_> = self.binders.into_iter().map(Some).collect();
+            for &slot in arrangement.slots.iter() {
+                binders.push(match slot {
+                    Slot::Widget(i) => widgets[i].take().unwrap(),
+                    Slot::Separator(_) => UiLoop::make_sep(gid.next(), gid.next()),
+                });
             }
+            spacer_i = arrangement.spacer;
+            slots = arrangement.slots;
         }
 
-        let spacer_i = left_i;
         let mut needs_update = true;
 
         'conrod: loop {
@@ -526,8 +684,9 @@ impl UiLoop {
                     DispResponse::ImageId(_) => (),
                     DispResponse::Event(event) => events.push(event),
                     DispResponse::WakeDisplay => {
-                        self.display_info.proxy.wakeup_event_loop();
+                        self.display_info.wakeup();
                     },
+                    DispResponse::Screenshot(shot, result) => self.screenshot(shot, result),
                 }
             }
 
@@ -538,8 +697,9 @@ impl UiLoop {
                     Ok(DispResponse::ImageId(_)) => (),
                     Ok(DispResponse::Event(event)) => events.push(event),
                     Ok(DispResponse::WakeDisplay) => {
-                        self.display_info.proxy.wakeup_event_loop();
+                        self.display_info.wakeup();
                     },
+                    Ok(DispResponse::Screenshot(shot, result)) => self.screenshot(shot, result),
                     Err(_) => break 'conrod,
                 }
             }
@@ -557,26 +717,32 @@ impl UiLoop {
                 None => self.display_info.width,
             };
 
-            binders.update_widths(&self.ui);
-            let totalw = binders.total_width();
-
-            let mut spacer_w = 0;
-            if totalw < bar_w {
-                spacer_w = bar_w - totalw;
-            } else {
-                // not implemented - we need to start chopping down size
-                // of widgets.
+            // size slots by what they drew last time.
+            for b in binders.iter_mut() {
+                let measured = match b.measure {
+                    Some(ref measure) => measure(&self.ui),
+                    None => self.ui.kids_bounding_box(b.id).map(|r| r.x.len() as u32),
+                };
+                b.width = layout::width(&b.layout, measured, b.width);
             }
 
-            let mut splits = Vec::with_capacity(binders.len() + 1); // + spacer
-
-            for &Binder{width: w, id, ..} in binders.iter() {
-                splits.push((id, Canvas::new().length(w as f64)));
+            let mut separators = vec![true; slots.len()];
+            for (slot, b) in slots.iter().zip(binders.iter()) {
+                if let Slot::Widget(i) = *slot {
+                    separators[i] = b.update.separator;
+                }
             }
 
-            splits.insert(
-                spacer_i, (spacer_id, Canvas::new().length(spacer_w as f64))
-            );
+            let mut widths: Vec<u32> = binders.iter().map(|b| b.width).collect();
+            layout::hide_separators(&slots, &mut widths, &separators);
+            let mut ids: Vec<Id> = binders.iter().map(|b| b.id).collect();
+            ids.insert(spacer_i, spacer_id);
+
+            // TODO overflowing slots should be shrunk rather than run off the end.
+            let splits: Vec<_> = ids.into_iter()
+                .zip(layout::place(&widths, spacer_i, bar_w))
+                .map(|(id, rect)| (id, Canvas::new().length(rect.w as f64)))
+                .collect();
 
             {
                 let mut ui = &mut self.ui.set_widgets();
@@ -593,17 +759,15 @@ impl UiLoop {
                     }
 
                     if let Some(dw) = update.width_update {
-                        if let Some(w) = b.layout.width {
-                            let mut neww = w as f64 + dw;
-                            if neww < 0. {
-                                neww = 0.;
-                            }
-                            b.layout = b.layout.with_width(Some(neww as u32));
-                        }
-
+                        b.layout = b.layout.resized(dw);
                         update.width_update = None;
                     }
 
+                    if let Some(minwidth) = update.minwidth_update {
+                        b.layout = b.layout.with_minwidth(minwidth);
+                        update.minwidth_update = None;
+                    }
+
                     b.update = update;
                 }
             }
@@ -615,7 +779,7 @@ impl UiLoop {
                     break 'conrod;
                 }
                 // Wakeup `winit` for rendering.
-                self.display_info.proxy.wakeup_event_loop();
+                self.display_info.wakeup();
 
             }
 
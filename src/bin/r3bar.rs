@@ -5,11 +5,30 @@ extern crate getopts;
 use conrod::color::{self, Color};
 use getopts::Options;
 use r3bar::bar;
+use r3bar::content::Content;
 use r3bar::error::BarError;
-use r3bar::gauges::{self, icon_text};
+use r3bar::i3bar::{self, Block, ClickEvent};
+use r3bar::lemonbar;
+use r3bar::gauges::{self, icon_text, Gauge};
+use r3bar::gauges::button_row::ButtonRowInput;
 use r3bar::message::{Message, WebpackInfo};
-use r3bar::sensors::{self, Sensor, i3workspace};
+use r3bar::sensors::{self, Level, Sensor, i3workspace};
+use r3bar::sensors::backlight::BacklightInfo;
+use r3bar::sensors::command::CommandOutput;
+use r3bar::sensors::cpu::CpuUsage;
+use r3bar::sensors::cpufreq::CpuFreqInfo;
+use r3bar::sensors::loadavg::LoadInfo;
+use r3bar::sensors::diskio::DeviceIo;
+use r3bar::sensors::diskusage::{MountUsage, Threshold};
+use r3bar::sensors::memory::MemInfo;
+use r3bar::sensors::netspeed::InterfaceRate;
+use r3bar::sensors::thermal::ThermalInfo;
+use r3bar::sensors::wifi::WifiStatus;
+use r3bar::sensors::wired::{InterfaceKind, InterfaceStatus};
+use r3bar::text;
+use std::io::{self, BufRead, Write};
 use std::path::{Path, PathBuf};
+use std::process;
 use std::sync::{Arc, Mutex, mpsc};
 use std::time::Duration;
 use std::{env, thread};
@@ -18,6 +37,10 @@ use std::collections::HashMap;
 static FONT_PATH: &'static str = "projects/r3bar/assets/fonts/Roboto Mono for Powerline.ttf";
 static BATTERY_PATH: &'static str = "projects/r3bar/assets/icons/battery";
 static VOLUME_PATH: &'static str = "projects/r3bar/assets/icons/volume";
+static MICROPHONE_PATH: &'static str = "projects/r3bar/assets/icons/microphone";
+
+// Called with the next platform profile, e.g. `r3bar-profile performance`.
+static PROFILE_HELPER: &'static str = "r3bar-profile";
 
 const BASE03: Color = Color::Rgba(0., 0.168627, 0.211764, 1.);
 const BASE02: Color = Color::Rgba(0.027450, 0.211764, 0.258823, 1.);
@@ -32,9 +55,17 @@ const CYAN: Color = Color::Rgba(0.164705, 0.631372, 0.596078, 1.);
 #[allow(dead_code)]
 const ORANGE: Color = Color::Rgba(0.796078, 0.294117, 0.086274, 1.);
 const MAGENTA: Color = Color::Rgba(0.827450, 0.211764, 0.509803, 1.);
+const YELLOW: Color = Color::Rgba(0.709803, 0.537254, 0., 1.);
+const RED: Color = Color::Rgba(0.862745, 0.196078, 0.184313, 1.);
 
 const BAR_HEIGHT: u32 = 26;
 
+// Slots bound per status line, blocks past this aren't shown.
+const MAX_STATUS_BLOCKS: usize = 16;
+
+// How long `--once` waits for sensors before printing.
+const ONCE_SETTLE_MS: u64 = 500;
+
 fn default_icon(id: conrod::image::Id) -> icon_text::Icon {
     icon_text::Icon {
         w: 24.0,
@@ -144,6 +175,48 @@ impl VolumeIcons {
     }
 }
 
+enum MicIcon {
+    Live,
+    Muted,
+    Recording,
+    None,
+}
+
+impl MicIcon {
+    pub fn to_struct(&self, mic: &MicIcons) -> icon_text::Icon {
+        match *self {
+            MicIcon::Live => mic.live,
+            MicIcon::Muted => mic.muted,
+            MicIcon::Recording => mic.recording,
+            MicIcon::None => mic.none,
+        }
+    }
+}
+
+struct MicIcons {
+    live: icon_text::Icon,
+    muted: icon_text::Icon,
+    recording: icon_text::Icon,
+    none: icon_text::Icon,
+}
+
+impl MicIcons {
+    pub fn new<F>(path_to_id: F) -> MicIcons
+        where F: Fn(PathBuf) -> Result<conrod::image::Id, BarError> {
+
+        let home = env::home_dir().unwrap();
+        let path = home.join(Path::new(MICROPHONE_PATH));
+        let convert = |p| default_icon(path_to_id(path.join(p)).unwrap());
+
+        MicIcons {
+            live: convert("mic-on.png"),
+            muted: convert("mic-muted.png"),
+            recording: convert("mic-recording.png"),
+            none: convert("no-mic.png"),
+        }
+    }
+}
+
 struct Battery {
     capacity: f64,
     icon: BatteryIcon,
@@ -154,6 +227,11 @@ struct Volume {
     icon: VolumeIcon,
 }
 
+struct Microphone {
+    percent: f64,
+    icon: MicIcon,
+}
+
 struct I3 {
     mode: String,
     workspaces: HashMap<String, Vec<(String, String, color::Color)>>,
@@ -163,18 +241,33 @@ struct State {
     battery: Battery,
     time: String,
     volume: Volume,
+    microphone: Microphone,
+    backlight: BacklightInfo,
     i3: I3,
     webpack: WebpackInfo,
     wifi: r3bar::sensors::wifi::WifiStatus,
-    diskusage: String,
+    wired: Vec<InterfaceStatus>,
+    netspeed: Vec<InterfaceRate>,
+    cpu: CpuUsage,
+    cpufreq: CpuFreqInfo,
+    loadavg: LoadInfo,
+    memory: MemInfo,
+    thermal: ThermalInfo,
+    diskusage: Vec<MountUsage>,
+    diskio: Vec<DeviceIo>,
     ticker: String,
+    commands: HashMap<String, CommandOutput>,
+    statuslines: HashMap<String, Vec<Block>>,
 }
 
 struct Store {
     tx: mpsc::Sender<Message>,
     rx: mpsc::Receiver<Message>,
     state: Arc<Mutex<State>>,
-    handles: Vec<thread::JoinHandle<Result<(), BarError>>>,
+    // sensor threads by the name they can be woken with.
+    handles: Vec<(String, thread::JoinHandle<Result<(), BarError>>)>,
+    // bar windows, for requests that go straight to them.
+    displays: Vec<mpsc::Sender<bar::DispResponse>>,
 }
 
 impl Store {
@@ -250,16 +343,72 @@ impl Store {
                 }
             }
 
-            Message::Unpark => {
-                for handle in self.handles.iter() {
+            Message::Unpark(None) => {
+                for &(_, ref handle) in self.handles.iter() {
                     handle.thread().unpark();
                 }
             }
 
+            Message::Unpark(Some(name)) => {
+                let mut found = false;
+                for &(_, ref handle) in self.handles.iter().filter(|&&(ref n, _)| *n == name) {
+                    handle.thread().unpark();
+                    found = true;
+                }
+                if !found {
+                    println!("unpark: no sensor named '{}'", name); // TODO logging
+                }
+            }
+
             Message::Wifi(status) => state.wifi = status,
 
+            Message::Wired(ifaces) => state.wired = ifaces,
+
+            Message::NetSpeed(speeds) => state.netspeed = speeds,
+
+            Message::Cpu(usage) => state.cpu = usage,
+
+            Message::Memory(info) => state.memory = info,
+
+            Message::Thermal(info) => state.thermal = info,
+
+            Message::CpuFreq(info) => state.cpufreq = info,
+
+            Message::LoadAvg(info) => state.loadavg = info,
+
+            Message::DiskIo(io) => state.diskio = io,
+
+            Message::Backlight(info) => state.backlight = info,
+
+            Message::Command(name, output) => {
+                state.commands.insert(name, output);
+            }
+
+            Message::StatusLine(name, blocks) => {
+                state.statuslines.insert(name, blocks);
+            }
+
             Message::Webpack(info) => state.webpack = info,
 
+            Message::Screenshot(shot, done) => {
+                let (result_tx, result_rx) = mpsc::channel();
+                for display in self.displays.iter() {
+                    let sent = display.send(
+                        bar::DispResponse::Screenshot(shot.clone(), result_tx.clone()));
+                    // the bar's window was closed.
+                    if sent.is_err() {
+                        result_tx.send(Some(Err("bar window has gone".to_owned()))).ok();
+                    }
+                }
+                drop(result_tx);
+
+                // each bar answers once it has saved, or skipped it.
+                thread::spawn(move || {
+                    let results: Vec<_> = result_rx.iter().collect();
+                    done.send(shot.outcome(&results)).ok();
+                });
+            }
+
             Message::Volume(volume) => {
                 match volume.parse::<f64>() {
                     Ok(vol) => {
@@ -280,6 +429,17 @@ impl Store {
                 }
             }
 
+            Message::Microphone(status) => {
+                state.microphone.percent = status.volume;
+                state.microphone.icon = if status.muted {
+                    MicIcon::Muted
+                } else if status.recording {
+                    MicIcon::Recording
+                } else {
+                    MicIcon::Live
+                }
+            }
+
             Message::DiskUsage(usage) => {
                 state.diskusage = usage;
             }
@@ -294,19 +454,28 @@ impl Store {
         };
     }
 
-    fn register<S: Sensor>(&mut self, sensor: &S) {
+    // `name` is what r3ipc UNPARK takes to wake just this sensor.
+    fn register<S: Sensor>(&mut self, name: &str, sensor: &S) {
         match sensor.run(self.tx.clone()) {
-            Ok(handle) => self.handles.push(handle),
+            Ok(handle) => self.handles.push((name.to_owned(), handle)),
             Err(e) => {
                 println!("{}", e);
             }
         }
     }
 
-    fn listen(self, ui_txs: Vec<mpsc::Sender<bar::DispResponse>>) -> thread::JoinHandle<()>
+    // Applies whatever messages are waiting without blocking.
+    fn drain(&self) {
+        while let Ok(msg) = self.rx.try_recv() {
+            self.update(msg);
+        }
+    }
+
+    // `on_update` runs after each message has been applied to the state.
+    fn listen<F>(self, mut on_update: F) -> thread::JoinHandle<()>
+        where F: FnMut() + Send + 'static
     {
         let listener = thread::spawn(move || {
-            let txs = ui_txs.clone();
             loop {
 
                 // channels will throw an error when the other ends disconnect.
@@ -316,10 +485,7 @@ impl Store {
                 };
 
                 self.update(msg);
-
-                for tx in txs.iter() {
-                    tx.send(bar::DispResponse::WakeDisplay).unwrap();
-                }
+                on_update();
             }
         });
 
@@ -344,10 +510,28 @@ fn main() {
     let args: Vec<String> = env::args().collect();
     let program = args[0].clone();
     let mut exit_seconds = None;
+    let mut profile_helper = PROFILE_HELPER.to_owned();
+    let mut backlight_helper = None;
+    let mut commands = Vec::new();
+    let mut persistent_commands = Vec::new();
+    let mut statuslines = Vec::new();
+    let mut i3bar_output = false;
+    let mut text_style = None;
+    let mut once = false;
+    let mut lemonbar_command = None;
 
     if args.len() > 1 {
         let mut opts = Options::new();
         opts.optopt("b", "bench", "run program for n seconds", "SECONDS");
+        opts.optopt("p", "profile-helper", "command run to switch power profile", "COMMAND");
+        opts.optopt("", "backlight-helper", "command run to set brightness without write access", "COMMAND");
+        opts.optmulti("c", "command", "show a command's output, i3blocks style, rerun every SECS (default 5, 0 only when unparked)", "NAME[:SECS]=COMMAND");
+        opts.optmulti("", "persist", "show a long running block script, sending it clicks", "NAME=COMMAND");
+        opts.optmulti("s", "statusline", "show an i3bar protocol producer such as i3status", "NAME=COMMAND");
+        opts.optflag("", "i3bar", "print i3bar protocol to stdout instead of opening a window");
+        opts.optopt("l", "lemonbar", "run lemonbar and feed it instead of opening a window", "COMMAND");
+        opts.optopt("t", "text", "print status lines for a terminal or tmux instead of opening a window", "plain|ansi|tmux");
+        opts.optflag("", "once", "with --text, print a single line and exit");
         opts.optflag("h", "help", "print this help menu");
         let matches = match opts.parse(&args[1..]) {
             Ok(m) => m,
@@ -363,6 +547,48 @@ fn main() {
             Some(seconds) => exit_seconds = Some(seconds.parse::<i32>().unwrap()),
             None => exit_seconds = None,
         }
+
+        if let Some(helper) = matches.opt_str("p") {
+            profile_helper = helper;
+        }
+
+        backlight_helper = matches.opt_str("backlight-helper");
+        i3bar_output = matches.opt_present("i3bar");
+        once = matches.opt_present("once");
+        lemonbar_command = matches.opt_str("l");
+
+        if let Some(style) = matches.opt_str("t") {
+            match style.parse::<text::Style>() {
+                Ok(style) => text_style = Some(style),
+                Err(e) => panic!(e.to_string()),
+            }
+        }
+
+        for spec in matches.opt_strs("c") {
+            match parse_command_spec(&spec) {
+                Some((name, interval, command)) => commands.push(
+                    sensors::command::CommandSensor::new(name, command, interval)),
+                None => panic!("--command expects NAME[:SECS]=COMMAND, got '{}'", spec),
+            }
+        }
+
+        for spec in matches.opt_strs("persist") {
+            let mut split = spec.splitn(2, '=');
+            match (split.next(), split.next()) {
+                (Some(name), Some(command)) => persistent_commands.push(
+                    sensors::command::PersistentCommand::new(name, command)),
+                _ => panic!("--persist expects NAME=COMMAND, got '{}'", spec),
+            }
+        }
+
+        for spec in matches.opt_strs("s") {
+            let mut split = spec.splitn(2, '=');
+            match (split.next(), split.next()) {
+                (Some(name), Some(command)) => statuslines.push(
+                    sensors::statusline::StatusLine::new(name, command)),
+                _ => panic!("--statusline expects NAME=COMMAND, got '{}'", spec),
+            }
+        }
     }
 
     let (tx, rx) = mpsc::channel();
@@ -379,21 +605,45 @@ fn main() {
         },
         webpack: WebpackInfo::Done,
         wifi: sensors::wifi::WifiStatus::new(53.),
+        wired: Vec::new(),
+        netspeed: Vec::new(),
+        cpu: CpuUsage::new(),
+        cpufreq: CpuFreqInfo::new(),
+        loadavg: LoadInfo::new(),
+        memory: MemInfo::new(),
+        thermal: ThermalInfo::new(),
         volume: Volume {
             percent: 0.,
             icon: VolumeIcon::None,
         },
-        diskusage: "".to_owned(),
+        microphone: Microphone {
+            percent: 0.,
+            icon: MicIcon::None,
+        },
+        backlight: BacklightInfo::new(),
+        diskusage: Vec::new(),
+        diskio: Vec::new(),
         ticker: "".to_owned(),
+        commands: HashMap::new(),
+        statuslines: HashMap::new(),
     }));
 
 
+    // sensors print their errors, which mustn't end up in the protocol or
+    // the status line we print.
+    let mut protocol_out = if i3bar_output || text_style.is_some() {
+        Some(i3bar::take_stdout().unwrap())
+    } else {
+        None
+    };
+
     // set up our store and start listening
     let mut store = Store {
         rx: rx,
         tx: tx.clone(),
         state: state.clone(),
         handles: Vec::new(),
+        displays: Vec::new(),
     };
 
     // set up the sensors
@@ -401,20 +651,181 @@ fn main() {
     let systime = sensors::systime::SysTime::new(Duration::from_millis(100));
     let battery = sensors::battery::Battery::new(Duration::from_millis(5000));
     let volume = sensors::volume::Volume::new(Duration::from_millis(10000));
+    let microphone = sensors::microphone::Microphone::new(Duration::from_millis(2000));
+    let mut backlight = sensors::backlight::Backlight::new(Duration::from_millis(30000));
+    if let Some(ref helper) = backlight_helper {
+        backlight = backlight.with_helper(helper);
+    }
     let ipc = sensors::ipc::Ipc::new(None).unwrap();
     let wifi = sensors::wifi::ConfigureWifi::new().unwrap().configure();
-    let diskusage = sensors::diskusage::DiskUsage::new(
-        Duration::from_millis(5000), vec!["/".to_owned(), "/home".to_owned()]
-    );
+    let wired = sensors::wired::Wired::new(Duration::from_millis(10000));
+    let netspeed = sensors::netspeed::NetSpeed::new(Duration::from_millis(2000), Vec::new());
+    let cpu = sensors::cpu::Cpu::new(Duration::from_millis(2000));
+    let cpufreq = sensors::cpufreq::CpuFreq::new(Duration::from_millis(5000));
+    let loadavg = sensors::loadavg::LoadAvg::new(Duration::from_millis(5000));
+    let memory = sensors::memory::Memory::new(Duration::from_millis(2000))
+        .with_urgent_below(1024 * 1024 * 1024);
+    let thermal = sensors::thermal::Thermal::new(Duration::from_millis(5000));
+    let diskusage = sensors::diskusage::DiskUsage::discover(Duration::from_millis(5000))
+        .with_default_threshold(Threshold::new(85., 95.));
+    let diskio = sensors::diskio::DiskIo::new(Duration::from_millis(2000), Vec::new());
+
+    store.register("volume", &volume);
+    store.register("microphone", &microphone);
+    store.register("backlight", &backlight);
+    store.register("systime", &systime);
+    store.register("ipc", &ipc);
+    store.register("i3workspace", &i3workspace);
+    store.register("battery", &battery);
+    store.register("wifi", &wifi);
+    store.register("wired", &wired);
+    store.register("netspeed", &netspeed);
+    store.register("cpu", &cpu);
+    store.register("cpufreq", &cpufreq);
+    store.register("loadavg", &loadavg);
+    store.register("memory", &memory);
+    store.register("thermal", &thermal);
+    store.register("diskusage", &diskusage);
+    store.register("diskio", &diskio);
+    for command in commands.iter() {
+        store.register(command.name(), command);
+    }
+    let command_names: Vec<String> = commands.iter().map(|c| c.name().to_owned()).collect();
+    for command in persistent_commands.iter() {
+        store.register(command.name(), command);
+    }
+    let clickers: Vec<(String, sensors::command::Clicker)> = persistent_commands.iter()
+        .map(|c| (c.name().to_owned(), c.clicker()))
+        .collect();
+    for statusline in statuslines.iter() {
+        store.register(statusline.name(), statusline);
+    }
+    let statusline_clickers: Vec<(String, sensors::statusline::StatusLineClicker)> = statuslines.iter()
+        .map(|s| (s.name().to_owned(), s.clicker()))
+        .collect();
+
+    // if there is an exit timer set it.
+    if let Some(seconds) = exit_seconds {
+        set_exit_timer(seconds as u64, tx.clone());
+    }
+
+    // a line of text for a terminal or tmux's status-right.
+    if let Some(style) = text_style {
+        let mut stdout = protocol_out.take().unwrap();
+
+        if once {
+            // give the sensors a moment to send their first readings.
+            thread::sleep(Duration::from_millis(ONCE_SETTLE_MS));
+            store.drain();
+            let line = text::render(&text_contents(&state.lock().unwrap()), style);
+            writeln!(stdout, "{}", line).unwrap();
+            return;
+        }
+
+        let mut last = String::new();
+        let state = state.clone();
+
+        let listener = store.listen(move || {
+            let line = text::render(&text_contents(&state.lock().unwrap()), style);
+
+            if line != last {
+                if let Err(e) = writeln!(stdout, "{}", line).and_then(|_| stdout.flush()) {
+                    println!("text output ERROR: {}", e);
+                    std::process::exit(1);
+                }
+                last = line;
+            }
+        });
 
-    store.register(&volume);
-    store.register(&systime);
-    store.register(&ipc);
-    store.register(&i3workspace);
-    store.register(&battery);
-    store.register(&wifi);
-    store.register(&diskusage);
+        listener.join();
+        return;
+    }
 
+    // lemonbar reads markup on stdin and prints click actions on stdout.
+    if let Some(command) = lemonbar_command {
+        let spawned = process::Command::new("sh")
+            .arg("-c")
+            .arg(&command)
+            .stdin(process::Stdio::piped())
+            .stdout(process::Stdio::piped())
+            .spawn();
+        let mut child = match spawned {
+            Ok(child) => child,
+            Err(e) => {
+                println!("lemonbar ERROR: couldn't run '{}': {}", command, e);
+                std::process::exit(1);
+            },
+        };
+        let (bar_out, mut bar_in) = match (child.stdout.take(), child.stdin.take()) {
+            (Some(bar_out), Some(bar_in)) => (bar_out, bar_in),
+            _ => {
+                println!("lemonbar ERROR: no pipes to '{}'", command);
+                std::process::exit(1);
+            },
+        };
+
+        let handlers = ClickHandlers {
+            backlight: backlight.clone(),
+            profile_helper: profile_helper.clone(),
+            clickers: clickers.clone(),
+            statusline_clickers: statusline_clickers.clone(),
+        };
+        read_clicks(bar_out, lemonbar::parse_action,
+                    handlers, state.clone(), tx.clone());
+
+        let mut last = String::new();
+        let state = state.clone();
+
+        let listener = store.listen(move || {
+            let line = lemonbar::render(&lemonbar_contents(&state.lock().unwrap()));
+
+            if line != last {
+                if let Err(e) = writeln!(bar_in, "{}", line).and_then(|_| bar_in.flush()) {
+                    // lemonbar has gone away.
+                    println!("lemonbar output ERROR: {}", e);
+                    std::process::exit(1);
+                }
+                last = line;
+            }
+        });
+
+        listener.join();
+        return;
+    }
+
+    // feed i3bar or swaybar rather than drawing our own window.
+    if i3bar_output {
+        let handlers = ClickHandlers {
+            backlight: backlight.clone(),
+            profile_helper: profile_helper.clone(),
+            clickers: clickers.clone(),
+            statusline_clickers: statusline_clickers.clone(),
+        };
+        read_clicks(io::stdin(), i3bar::parse_click_line, handlers, state.clone(), tx.clone());
+
+        let header = i3bar::Header{click_events: true, ..i3bar::Header::new()};
+        let stdout = protocol_out.take().unwrap();
+        let mut writer = i3bar::Writer::new(stdout, &header).unwrap();
+        let mut last = Vec::new();
+        let state = state.clone();
+
+        let listener = store.listen(move || {
+            let blocks = status_blocks(&state.lock().unwrap());
+
+            // the clock alone sends ten updates a second.
+            if blocks != last {
+                if let Err(e) = writer.write(&blocks) {
+                    // i3bar has gone away.
+                    println!("i3bar output ERROR: {}", e);
+                    std::process::exit(1);
+                }
+                last = blocks;
+            }
+        });
+
+        listener.join();
+        return;
+    }
 
     // instantiate a our system
     let r3b = r3bar::bar::Bar{};
@@ -431,6 +842,7 @@ fn main() {
 
         let volume_icons;
         let battery_icons;
+        let mic_icons;
         {
             let loader = |p| ui_context.load_image(p);
             volume_icons = VolumeIcons::new(loader);
@@ -441,14 +853,32 @@ fn main() {
             battery_icons = BatteryIcons::new(loader);
         }
 
+        {
+            let loader = |p| ui_context.load_image(p);
+            mic_icons = MicIcons::new(loader);
+        }
+
         let time_widget;
         let battery_widget;
         let wifi_widget;
+        let wired_widget;
+        let netspeed_widget;
+        let cpu_widget;
+        let cpu_bars;
+        let cpufreq_widget;
+        let loadavg_widget;
+        let memory_widget;
+        let thermal_widget;
         let workspace_widget;
         let redkitt;
         let volume_widget;
+        let mic_widget;
+        let backlight_widget;
         let diskusage_widget;
+        let diskio_widget;
         let ticker_widget;
+        let command_widgets: Vec<(String, icon_text::IconText)>;
+        let persist_widgets: Vec<(String, sensors::command::Clicker, icon_text::IconText)>;
         {
             let ui = &mut ui_context.ui;
 
@@ -464,120 +894,201 @@ fn main() {
             time_widget = gauges::icon_text::IconText::new(ui.widget_id_generator());
             battery_widget = gauges::icon_text::IconText::new(ui.widget_id_generator());
             wifi_widget = gauges::icon_text::IconText::new(ui.widget_id_generator());
+            wired_widget = gauges::icon_text::IconText::new(ui.widget_id_generator());
+            netspeed_widget = gauges::icon_text::IconText::new(ui.widget_id_generator());
+            cpu_widget = gauges::icon_text::IconText::new(ui.widget_id_generator());
+            cpu_bars = gauges::bars::Bars::new(64, 4., BASE0, ui.widget_id_generator());
+            cpufreq_widget = gauges::icon_text::IconText::new(ui.widget_id_generator());
+            loadavg_widget = gauges::icon_text::IconText::new(ui.widget_id_generator());
+            memory_widget = gauges::icon_text::IconText::new(ui.widget_id_generator());
+            thermal_widget = gauges::icon_text::IconText::new(ui.widget_id_generator());
             ticker_widget = gauges::icon_text::IconText::new(ui.widget_id_generator());
             workspace_widget = gauges::button_row::ButtonRow::new(
                 30, BASE03, MAGENTA, ui.widget_id_generator()
             );
             redkitt = gauges::redkitt::RedKitt::new(ui.widget_id_generator());
             volume_widget = gauges::icon_text::IconText::new(ui.widget_id_generator());
+            mic_widget = gauges::icon_text::IconText::new(ui.widget_id_generator());
+            backlight_widget = gauges::icon_text::IconText::new(ui.widget_id_generator());
             diskusage_widget = gauges::icon_text::IconText::new(ui.widget_id_generator());
+            diskio_widget = gauges::icon_text::IconText::new(ui.widget_id_generator());
+            command_widgets = command_names.iter()
+                .map(|name| (name.clone(), gauges::icon_text::IconText::new(ui.widget_id_generator())))
+                .collect();
+            persist_widgets = clickers.iter()
+                .map(|&(ref name, ref clicker)| (name.clone(), clicker.clone(),
+                                                 gauges::icon_text::IconText::new(ui.widget_id_generator())))
+                .collect();
         }
 
         // TIME
         let dwidth = bar::DEFAULT_GAUGE_WIDTH;
-        {
-            let state = state.clone();
-            ui_context.bind(
-                r3bar::Layout::new(),
-                move |slot_id, mut ui_widgets, update| {
-
-                    let state = state.lock().unwrap();
-
-                    time_widget.render(icon_text::Opts{
-                        maybe_icon: None,
-                        maybe_text: Some(&state.time),
-                    }, slot_id, ui_widgets);
-
-                    update
-                });
-        }
+        bind_gauge(ui_context, r3bar::Layout::new(), state.clone(), time_widget,
+                   |state| text_opts(None, time_content(state)),
+                   |_| ());
 
         // BATTERY
-        {
-            let state = state.clone();
-            ui_context.bind(
-                r3bar::Layout::new(),
-                move |slot_id, mut ui_widgets, update| {
-
-                    let state = state.lock().unwrap();
-                    let battery_icon = state.battery.icon.to_struct(&battery_icons);
-
-                    battery_widget.render(icon_text::Opts{
-                        maybe_icon: Some(battery_icon),
-                        maybe_text: Some(&format!("{}%", state.battery.capacity)),
-                    }, slot_id, ui_widgets);
-
-                    update
-                });
-        }
+        bind_gauge(ui_context, r3bar::Layout::new(), state.clone(), battery_widget,
+                   move |state| text_opts(Some(state.battery.icon.to_struct(&battery_icons)),
+                                          battery_content(state)),
+                   |_| ());
 
         // DISK USAGE
-        {
-            let state = state.clone();
-
-            ui_context.bind(
-                r3bar::Layout::new(),
-                move |slot_id, mut ui_widgets, update| {
-
-                    let state = state.lock().unwrap();
+        bind_gauge(ui_context, r3bar::Layout::new(), state.clone(), diskusage_widget,
+                   |state| text_opts(None, disk_content(state)),
+                   |_| ());
 
-                    diskusage_widget.render(icon_text::Opts{
-                        maybe_icon: None,
-                        maybe_text: Some(&state.diskusage),
-                    }, slot_id, ui_widgets);
+        // VOLUME
+        bind_gauge(ui_context, r3bar::Layout::new(), state.clone(), volume_widget,
+                   move |state| text_opts(Some(state.volume.icon.to_struct(&volume_icons)),
+                                          volume_content(state)),
+                   |_| ());
 
-                    update
-                });
+        // MICROPHONE
+        {
+            let app_tx = app_tx.clone();
+
+            bind_gauge(ui_context, r3bar::Layout::new(), state.clone(), mic_widget,
+                       move |state| text_opts(Some(state.microphone.icon.to_struct(&mic_icons)),
+                                              microphone_content(state)),
+                       move |event| if event.button == 1 {
+                           let app_tx = app_tx.clone();
+
+                           // pactl can be slow to return, keep it off the UI thread.
+                           thread::spawn(move || {
+                               match sensors::microphone::Microphone::toggle_mute() {
+                                   // wake the sensor so the new state shows immediately
+                                   Ok(_) => app_tx.send(Message::Unpark(Some("microphone".to_owned()))).unwrap(),
+                                   Err(e) => println!("{}", e), // TODO logging
+                               }
+                           });
+                       });
         }
 
-        // VOLUME
+        // BACKLIGHT
         {
-            let state = state.clone();
-
-            ui_context.bind(
-                r3bar::Layout::new(),
-                move |slot_id, mut ui_widgets, update| {
-
-                    let state = state.lock().unwrap();
-                    let volume_icon = state.volume.icon.to_struct(&volume_icons);
-                    volume_widget.render(icon_text::Opts{
-                        maybe_icon: Some(volume_icon),
-                        maybe_text: Some(&format!("{}%", state.volume.percent)),
-                    }, slot_id, ui_widgets);
-
-                    update
-                });
+            let backlight = backlight.clone();
+
+            bind_gauge(ui_context, r3bar::Layout::new(), state.clone(), backlight_widget,
+                       |state| text_opts(None, backlight_content(state)),
+                       move |event| {
+                           // the sensor watches the brightness file so needs no waking
+                           let step = match event.button {
+                               4 => 5.,
+                               5 => -5.,
+                               _ => return,
+                           };
+                           // a helper may be slow to run, keep it off the UI thread.
+                           let backlight = backlight.clone();
+                           thread::spawn(move || if let Err(e) = backlight.adjust(step) {
+                               println!("{}", e); // TODO logging
+                           });
+                       });
         }
 
         // WIFI
+        bind_gauge(ui_context, r3bar::Layout::new(), state.clone(), wifi_widget,
+                   |state| text_opts(None, wifi_content(state)),
+                   |_| ());
+
+        // WIRED & VPN
+        bind_gauge(ui_context, r3bar::Layout::new(), state.clone(), wired_widget,
+                   |state| text_opts(None, wired_content(state)),
+                   |_| ());
+
+        // NETWORK THROUGHPUT
+        bind_gauge(ui_context, r3bar::Layout::new(), state.clone(), netspeed_widget,
+                   |state| text_opts(None, netspeed_content(state)),
+                   |_| ());
+
+        // DISK I/O
+        bind_gauge(ui_context, r3bar::Layout::new(), state.clone(), diskio_widget,
+                   |state| text_opts(None, diskio_content(state)),
+                   |_| ());
+
+        // CPU
+        bind_gauge(ui_context, r3bar::Layout::new(), state.clone(), cpu_widget,
+                   |state| text_opts(None, cpu_content(state)),
+                   |_| ());
+
+        bind_gauge(ui_context, r3bar::Layout::new(), state.clone(), cpu_bars,
+                   |state| state.cpu.cores.iter().map(|c| c.busy).collect(),
+                   |_| ());
+
+        // MEMORY
+        bind_gauge(ui_context, r3bar::Layout::new(), state.clone(), memory_widget,
+                   |state| text_opts(None, memory_content(state)),
+                   |_| ());
+
+        // CPU FREQUENCY & POWER PROFILE
         {
             let state = state.clone();
+            let app_tx = app_tx.clone();
+            let profile_helper = profile_helper.clone();
+
+            bind_gauge(ui_context, r3bar::Layout::new(), state.clone(), cpufreq_widget,
+                       |state| text_opts(None, cpufreq_content(state)),
+                       move |event| if event.button == 1 {
+                           let next = state.lock().unwrap().cpufreq.next_profile();
+                           if let Some(profile) = next {
+                               let app_tx = app_tx.clone();
+                               let profile_helper = profile_helper.clone();
+
+                               // the helper may ask for a password, keep it off the UI thread.
+                               thread::spawn(move || {
+                                   match sensors::cpufreq::CpuFreq::set_profile(&profile_helper, &profile) {
+                                       // wake the sensor so the new profile shows immediately
+                                       Ok(_) => app_tx.send(Message::Unpark(Some("cpufreq".to_owned()))).unwrap(),
+                                       Err(e) => println!("{}", e), // TODO logging
+                                   }
+                               });
+                           }
+                       });
+        }
 
-            ui_context.bind(
-                r3bar::Layout::new(),
-                move |slot_id, mut ui_widgets, update| {
-
-                    let state = state.lock().unwrap();
-                    let ssid = state.wifi.ssid.clone()
-                        .unwrap_or("unconnected".to_owned());
-
-                    let sigq = state.wifi.signal.map(dbm_to_percent)
-                        .map(|p| format!(" {}%", p))
-                        .unwrap_or("".to_owned());
-
-                    let ip = state.wifi.ip.clone()
-                        .map(|s| format!(" - {}",  s))
-                        .unwrap_or("".to_owned());
-
-                    let wifi_line = format!("{}{}{}", ssid, sigq, ip);
+        // LOAD & UPTIME
+        bind_gauge(ui_context, r3bar::Layout::new(), state.clone(), loadavg_widget,
+                   |state| text_opts(None, loadavg_content(state)),
+                   |_| ());
+
+        // TEMPERATURE & FANS
+        bind_gauge(ui_context, r3bar::Layout::new(), state.clone(), thermal_widget,
+                   |state| text_opts(None, thermal_content(state)),
+                   |_| ());
+
+        // COMMANDS
+        for (name, command_widget) in command_widgets {
+            bind_gauge(ui_context, r3bar::Layout::new(), state.clone(), command_widget,
+                       move |state| match state.commands.get(&name) {
+                           Some(output) => text_opts(None, command_content(&name, output)),
+                           None => icon_text::Opts::default(),
+                       },
+                       |_| ());
+        }
 
-                    wifi_widget.render(icon_text::Opts{
-                        maybe_icon: None,
-                        maybe_text: Some(&wifi_line),
-                    }, slot_id, ui_widgets);
+        // PERSISTENT COMMANDS
+        for (name, clicker, command_widget) in persist_widgets {
+            bind_gauge(ui_context, r3bar::Layout::new(), state.clone(), command_widget,
+                       move |state| match state.commands.get(&name) {
+                           Some(output) => text_opts(None, command_content(&name, output)),
+                           None => icon_text::Opts::default(),
+                       },
+                       move |event| if let Err(e) = clicker.click(&event) {
+                           println!("{}", e); // TODO logging
+                       });
+        }
 
-                    update
-                });
+        // STATUS LINES
+        for &(ref name, ref clicker) in statusline_clickers.iter() {
+            let state = state.clone();
+            let name = name.clone();
+            let clicker = clicker.clone();
+            gauges::blocks::bind(ui_context, r3bar::Layout::new(), MAX_STATUS_BLOCKS, RED,
+                                 move || state.lock().unwrap().statuslines.get(&name)
+                                     .cloned().unwrap_or(Vec::new()),
+                                 move |event| if let Err(e) = clicker.click(&event) {
+                                     println!("{}", e); // TODO logging
+                                 });
         }
 
         // WEBPACK SENSOR
@@ -595,9 +1106,7 @@ fn main() {
                         _ => None,
                     };
 
-                    if let Some(_) = redkitt.render(
-                        slot_id, ui_widgets, delta
-                    ) {
+                    for _ in redkitt.render(delta, slot_id, ui_widgets) {
                         let done_msg = Message::Webpack(WebpackInfo::Done);
                         if let Err(e) = app_tx.send(done_msg) {
                             println!("{}", e); // logging
@@ -609,73 +1118,562 @@ fn main() {
         }
 
         // WIFI
+        bind_gauge(ui_context, r3bar::Layout::new().with_minwidth(Some(dwidth)), state.clone(),
+                   ticker_widget,
+                   |state| text_opts(None, ticker_content(state)),
+                   |_| ());
+
+
+        // I3 WORKSPACES
         {
-            let state = state.clone();
+            let workspace_output = output.clone();
+
+            bind_gauge(ui_context,
+                       workspace_layout(),
+                       state.clone(),
+                       workspace_widget,
+                       move |state| ButtonRowInput {
+                           buttons: match state.i3.workspaces.get(&workspace_output) {
+                               Some(workspaces) => workspaces.clone(),
+                               None => Vec::new(),
+                           },
+                           label: state.i3.mode.clone(),
+                       },
+                       move |workspace_name| {
+                           let r = i3workspace::I3Workspace::change_workspace(
+                               workspace_name, output.clone()
+                           );
+                           if let Err(e) = r {
+                               println!("{}", e); // TODO logging
+                           }
+                       });
+        }
+    }));
 
-            ui_context.bind(
-                r3bar::Layout::new().with_minwidth(Some(dwidth)),
-                move |slot_id, mut ui_widgets, update| {
+    store.displays = ui_txs.clone();
+    let listener = store.listen(move || {
+        for tx in ui_txs.iter() {
+            tx.send(bar::DispResponse::WakeDisplay).unwrap();
+        }
+    });
 
-                    let state = state.lock().unwrap();
-                    let ticker = state.ticker.clone();
+    listener.join();
+}
 
-                    ticker_widget.render(icon_text::Opts{
-                        maybe_icon: None,
-                        maybe_text: Some(&ticker),
-                    }, slot_id, ui_widgets);
+// "NAME[:SECS]=COMMAND". Without SECS commands rerun every 5 seconds, with
+// SECS of 0 only at start up and when unparked.
+fn parse_command_spec(spec: &str) -> Option<(&str, Option<Duration>, &str)> {
+    let mut split = spec.splitn(2, '=');
+    let (target, command) = match (split.next(), split.next()) {
+        (Some(target), Some(command)) => (target, command),
+        _ => return None,
+    };
 
-                    update
-                });
-        }
+    let mut split = target.splitn(2, ':');
+    let name = match split.next() {
+        Some(name) if !name.is_empty() => name,
+        _ => return None,
+    };
 
+    let interval = match split.next() {
+        None => Some(Duration::from_millis(5000)),
+        Some(secs) => match secs.parse::<u64>() {
+            Ok(0) => None,
+            Ok(secs) => Some(Duration::from_secs(secs)),
+            Err(_) => return None,
+        },
+    };
 
-        // I3 WORKSPACES
-        {
-            let state = state.clone();
+    Some((name, interval, command))
+}
 
-            ui_context.bind(
-                r3bar::Layout::new()
-                    .with_minwidth(Some(dwidth + dwidth / 2))
-                    .with_orientation(r3bar::Orientation::Left),
-                move |slot_id, mut ui_widgets, update| {
+// Binds `gauge` to a slot sized by what it measures, feeding it what
+// `input` makes of the state each frame and handing whatever it reports to
+// `on_event`.
+fn bind_gauge<G, I, E>(ui_context: &mut bar::UiLoop,
+                       layout: r3bar::Layout,
+                       state: Arc<Mutex<State>>,
+                       gauge: G,
+                       input: I,
+                       on_event: E)
+    where G: Gauge + Send + Sync + 'static,
+          I: Fn(&State) -> G::Input + Send + 'static,
+          E: Fn(G::Event) + Send + 'static
+{
+    let gauge = Arc::new(gauge);
+    let measured = gauge.clone();
+
+    ui_context.bind_measured(layout, move |slot_id, ui_widgets, update| {
+        // the lock is released before handling events as they may need it.
+        let gauge_input = input(&state.lock().unwrap());
+
+        for event in gauge.render(gauge_input, slot_id, ui_widgets) {
+            on_event(event);
+        }
 
-                    let state = state.lock().unwrap();
-                    let workspaces = match state.i3.workspaces.get(&output) {
-                        Some(workspaces) => workspaces.clone(),
-                        None => Vec::new(),
-                    };
+        update
+    }, move |ui| measured.measure(ui).map(|dim| dim[0] as u32));
+}
 
-                    let maybe_clicked = workspace_widget
-                        .render(
-                            workspaces,
-                            &state.i3.mode,
-                            slot_id,
-                            ui_widgets);
-
-                    if let Some(workspace_name) = maybe_clicked {
-                        let r = i3workspace::I3Workspace::change_workspace(
-                            workspace_name, output.clone()
-                        );
-                        if let Err(e) = r {
-                            println!("{}", e); // TODO logging
-                        }
+// What clicks from i3bar can act on.
+struct ClickHandlers {
+    backlight: sensors::backlight::Backlight,
+    profile_helper: String,
+    clickers: Vec<(String, sensors::command::Clicker)>,
+    statusline_clickers: Vec<(String, sensors::statusline::StatusLineClicker)>,
+}
+
+// Clicks arrive a line at a time, from i3bar on stdin or from lemonbar.
+fn read_clicks<R, P>(input: R,
+                     parse: P,
+                     handlers: ClickHandlers,
+                     state: Arc<Mutex<State>>,
+                     tx: mpsc::Sender<Message>)
+    where R: io::Read + Send + 'static,
+          P: Fn(&str) -> Result<Option<ClickEvent>, BarError> + Send + 'static
+{
+    thread::spawn(move || {
+        for line in io::BufReader::new(input).lines() {
+            let event = match line.map_err(BarError::from)
+                .and_then(|l| parse(&l)) {
+                    Ok(Some(event)) => event,
+                    Ok(None) => continue,
+                    Err(e) => {
+                        println!("{}", e); // TODO logging
+                        continue;
                     }
+                };
 
-                    update
-                });
+            match dispatch_click(&event, &handlers, &state) {
+                // wake the sensors so the result shows immediately
+                Ok(_) => tx.send(Message::Unpark(None)).unwrap(),
+                Err(e) => println!("{}", e), // TODO logging
+            }
         }
-    }));
+    });
+}
 
-    let listener = store.listen(ui_txs);
+fn dispatch_click(event: &ClickEvent,
+                  handlers: &ClickHandlers,
+                  state: &Arc<Mutex<State>>) -> Result<(), BarError> {
+
+    // status line blocks carry "<statusline>:<instance>", see `status_blocks`.
+    if let Some(instance) = event.instance.as_ref() {
+        let mut split = instance.splitn(2, ':');
+        if let (Some(name), Some(rest)) = (split.next(), split.next()) {
+            if let Some(&(_, ref clicker)) = handlers.statusline_clickers.iter()
+                .find(|&&(ref n, _)| n == name) {
+                    let mut event = event.clone();
+                    event.instance = if rest.is_empty() { None } else { Some(rest.to_owned()) };
+                    return clicker.click(&event);
+                }
+        }
+    }
 
-    // if there is an exit timer set it.
-    if let Some(seconds) = exit_seconds {
-        set_exit_timer(seconds as u64, tx.clone());
+    match (event.name.as_ref(), event.button) {
+        // lemonbar workspaces carry "<output>:<workspace>", see `workspace_contents`.
+        ("workspace", 1) => match event.instance.as_ref().map(|i| i.splitn(2, ':')) {
+            Some(mut split) => match (split.next(), split.next()) {
+                (Some(output), Some(workspace)) => i3workspace::I3Workspace::change_workspace(
+                    workspace.to_owned(), output.to_owned()),
+                _ => Ok(()),
+            },
+            None => Ok(()),
+        },
+        ("microphone", 1) => sensors::microphone::Microphone::toggle_mute(),
+        ("cpufreq", 1) => {
+            let next = state.lock().unwrap().cpufreq.next_profile();
+            match next {
+                Some(profile) => sensors::cpufreq::CpuFreq::set_profile(
+                    &handlers.profile_helper, &profile),
+                None => Ok(()),
+            }
+        }
+        ("backlight", 4) => handlers.backlight.adjust(5.),
+        ("backlight", 5) => handlers.backlight.adjust(-5.),
+        (name, _) => match handlers.clickers.iter().find(|&&(ref n, _)| n == name) {
+            Some(&(_, ref clicker)) => clicker.click(event),
+            None => Ok(()),
+        },
     }
+}
 
-    listener.join();
+// What each widget shows, in the order the window draws them. i3bar
+// draws workspaces itself so they're left out.
+fn widget_contents(state: &State) -> Vec<Content> {
+    let mut contents = vec![
+        time_content(state),
+        battery_content(state),
+        disk_content(state),
+        volume_content(state),
+        microphone_content(state),
+        backlight_content(state),
+        wifi_content(state),
+        wired_content(state),
+        netspeed_content(state),
+        diskio_content(state),
+        cpu_content(state),
+        memory_content(state),
+        cpufreq_content(state),
+        loadavg_content(state),
+        thermal_content(state),
+    ];
+
+    let mut names: Vec<&String> = state.commands.keys().collect();
+    names.sort();
+    for name in names {
+        contents.push(command_content(name, &state.commands[name]));
+    }
+
+    contents.push(ticker_content(state));
+    contents
+}
+
+// Status line blocks are passed through whole, with the producer's name
+// added to the instance so clicks can find their way back.
+fn statusline_blocks(state: &State) -> Vec<Block> {
+    let mut blocks = Vec::new();
+
+    let mut names: Vec<&String> = state.statuslines.keys().collect();
+    names.sort();
+    for name in names {
+        for b in state.statuslines[name].iter() {
+            let mut b = b.clone();
+            let instance = b.instance.take().unwrap_or("".to_owned());
+            b.instance = Some(format!("{}:{}", name, instance));
+            blocks.push(b);
+        }
+    }
+
+    blocks
+}
+
+fn status_blocks(state: &State) -> Vec<Block> {
+    let mut blocks: Vec<Block> = widget_contents(state).iter()
+        .map(|c| c.to_block())
+        .collect();
+    blocks.extend(statusline_blocks(state));
+
+    // i3bar draws nothing for empty blocks but still leaves a separator.
+    blocks.retain(|b| !b.full_text.is_empty());
+    blocks
+}
+
+// Where the workspaces go on the bar, and so in the other outputs too.
+fn workspace_layout() -> r3bar::Layout {
+    let dwidth = bar::DEFAULT_GAUGE_WIDTH;
+    r3bar::Layout::new()
+        .with_minwidth(Some(dwidth + dwidth / 2))
+        .with_orientation(r3bar::Orientation::Left)
+}
+
+// Workspaces one at a time so each can be clicked, preceded by the mode.
+fn workspace_contents(state: &State) -> Vec<Content> {
+    let orientation = workspace_layout().orientation();
+    let mut contents = vec![
+        Content::new("mode", state.i3.mode.clone()).with_orientation(orientation),
+    ];
+
+    let mut outputs: Vec<&String> = state.i3.workspaces.keys().collect();
+    outputs.sort();
+    for output in outputs {
+        for &(ref name, _, color) in state.i3.workspaces[output].iter() {
+            contents.push(Content::new("workspace", name.clone())
+                          .with_instance(Some(format!("{}:{}", output, name)))
+                          .with_color(Some(color))
+                          .with_orientation(orientation));
+        }
+    }
+
+    contents
+}
+
+fn lemonbar_contents(state: &State) -> Vec<Content> {
+    let mut contents = workspace_contents(state);
+    contents.extend(widget_contents(state));
+    contents.extend(statusline_blocks(state).iter().map(Content::from_block));
+    contents
+}
+
+fn text_contents(state: &State) -> Vec<Content> {
+    let mut contents = vec![workspace_content(state)];
+    contents.extend(widget_contents(state));
+    contents.extend(statusline_blocks(state).iter().map(Content::from_block));
+    contents
+}
+
+// Urgent content is drawn red on the bar.
+fn display_color(content: &Content) -> Option<Color> {
+    if content.urgent { Some(RED) } else { content.color }
+}
+
+fn text_opts(icon: Option<icon_text::Icon>, content: Content) -> icon_text::Opts {
+    icon_text::Opts {
+        maybe_icon: icon,
+        maybe_color: display_color(&content),
+        maybe_text: Some(content.text),
+    }
+}
+
+fn time_content(state: &State) -> Content {
+    Content::new("time", state.time.clone())
+}
+
+fn battery_content(state: &State) -> Content {
+    Content::new("battery", format!("{}%", state.battery.capacity))
+}
+
+fn disk_content(state: &State) -> Content {
+    Content::new("disk", format_disk_usage(&state.diskusage))
+        .with_color(disk_color(&state.diskusage))
+}
+
+fn volume_content(state: &State) -> Content {
+    Content::new("volume", format!("{}%", state.volume.percent))
+}
+
+fn microphone_content(state: &State) -> Content {
+    Content::new("microphone", format!("{}%", state.microphone.percent))
+}
+
+fn backlight_content(state: &State) -> Content {
+    Content::new("backlight", format!("☀ {:.0}%", state.backlight.percent))
+}
+
+fn wifi_content(state: &State) -> Content {
+    Content::new("wifi", format_wifi(&state.wifi))
+}
+
+fn wired_content(state: &State) -> Content {
+    Content::new("wired", format_wired(&state.wired))
+}
+
+fn netspeed_content(state: &State) -> Content {
+    Content::new("netspeed", format_netspeed(&state.netspeed))
+}
+
+fn diskio_content(state: &State) -> Content {
+    Content::new("diskio", format_diskio(&state.diskio))
+        .with_color(diskio_color(&state.diskio))
+}
+
+fn cpu_content(state: &State) -> Content {
+    Content::new("cpu", format!("CPU {:.0}%", state.cpu.total.busy))
+}
+
+fn memory_content(state: &State) -> Content {
+    Content::new("memory", format_memory(&state.memory))
+        .with_urgent(state.memory.urgent)
+}
+
+fn cpufreq_content(state: &State) -> Content {
+    Content::new("cpufreq", format_cpufreq(&state.cpufreq))
+}
+
+fn loadavg_content(state: &State) -> Content {
+    Content::new("loadavg", format_loadavg(&state.loadavg))
+        .with_color(loadavg_color(&state.loadavg))
+}
+
+fn thermal_content(state: &State) -> Content {
+    Content::new("thermal", format_thermal(&state.thermal))
+        .with_color(thermal_color(&state.thermal))
+}
+
+fn command_content(name: &str, output: &CommandOutput) -> Content {
+    let mut content = Content::new(name, output.full_text.clone())
+        .with_color(output.color.as_ref().and_then(|c| i3bar::parse_color(c)))
+        .with_urgent(output.urgent);
+    content.short_text = output.short_text.clone();
+    content
+}
+
+fn ticker_content(state: &State) -> Content {
+    Content::new("ticker", state.ticker.clone())
+}
+
+// "[resize] 1 2 3", every output's workspaces as there's no monitor to
+// pick one by.
+fn workspace_content(state: &State) -> Content {
+    let mut outputs: Vec<&String> = state.i3.workspaces.keys().collect();
+    outputs.sort();
+
+    let mut names: Vec<String> = Vec::new();
+    if !state.i3.mode.is_empty() {
+        names.push(format!("[{}]", state.i3.mode));
+    }
+    for output in outputs {
+        names.extend(state.i3.workspaces[output].iter().map(|w| w.0.clone()));
+    }
+
+    Content::new("workspaces", names.join(" "))
+        .with_orientation(workspace_layout().orientation())
 }
 
 fn dbm_to_percent(dbm: f64) -> f64 {
     2. * (dbm + 100.)
 }
+
+// "ssid 80% - 192.168.1.2"
+fn format_wifi(wifi: &WifiStatus) -> String {
+    let ssid = wifi.ssid.clone()
+        .unwrap_or("unconnected".to_owned());
+
+    let sigq = wifi.signal.map(dbm_to_percent)
+        .map(|p| format!(" {}%", p))
+        .unwrap_or("".to_owned());
+
+    let ip = wifi.ip.as_ref()
+        .and_then(|addrs| addrs.primary())
+        .map(|s| format!(" - {}",  s))
+        .unwrap_or("".to_owned());
+
+    format!("{}{}{}", ssid, sigq, ip)
+}
+
+fn format_netspeed(rates: &[InterfaceRate]) -> String {
+    let (rx, tx) = rates.iter()
+        .fold((0., 0.), |(rx, tx), r| (rx + r.rx, tx + r.tx));
+
+    format!("↓ {} ↑ {}", format_rate(rx), format_rate(tx))
+}
+
+// Totals across disks and the busiest disk's utilisation.
+fn format_diskio(io: &[DeviceIo]) -> String {
+    let (read, write) = io.iter()
+        .fold((0., 0.), |(r, w), d| (r + d.read, w + d.write));
+
+    format!("R {} W {} {:.0}%", format_rate(read), format_rate(write), diskio_busy(io))
+}
+
+fn diskio_busy(io: &[DeviceIo]) -> f64 {
+    io.iter().fold(0., |b: f64, d| b.max(d.busy))
+}
+
+// a disk that can't keep up is what makes everything else crawl
+fn diskio_color(io: &[DeviceIo]) -> Option<Color> {
+    if diskio_busy(io) >= 90. { Some(YELLOW) } else { None }
+}
+
+fn format_memory(mem: &MemInfo) -> String {
+    let swap = match mem.swap_used {
+        0 => "".to_owned(),
+        used => format!(" SWP {}", format_bytes(used)),
+    };
+    let zram = mem.zram
+        .map(|z| format!(" ZRAM {}", format_bytes(z.used)))
+        .unwrap_or("".to_owned());
+
+    format!("MEM {}/{}{}{}", format_bytes(mem.used), format_bytes(mem.total), swap, zram)
+}
+
+fn format_loadavg(load: &LoadInfo) -> String {
+    format!("{:.2} {:.2} {:.2} up {}",
+            load.one, load.five, load.fifteen, format_uptime(load.uptime))
+}
+
+fn loadavg_color(load: &LoadInfo) -> Option<Color> {
+    level_color(load.level)
+}
+
+fn format_thermal(info: &ThermalInfo) -> String {
+    let mut parts: Vec<String> = info.temperatures.iter()
+        .map(|t| format!("{:.0}°C", t.celsius))
+        .collect();
+    parts.extend(info.fans.iter().map(|f| format!("{}rpm", f.rpm)));
+    parts.join(" ")
+}
+
+fn thermal_color(info: &ThermalInfo) -> Option<Color> {
+    level_color(info.level())
+}
+
+fn format_disk_usage(usages: &[MountUsage]) -> String {
+    usages.iter()
+        .map(|u| format!("{} {:.0}%", u.mountpoint, u.percent))
+        .collect::<Vec<_>>()
+        .join("  ")
+}
+
+// Color the whole widget by its fullest mount.
+fn disk_color(usages: &[MountUsage]) -> Option<Color> {
+    usages.iter().map(|u| u.level).max().and_then(level_color)
+}
+
+fn level_color(level: Level) -> Option<Color> {
+    match level {
+        Level::Critical => Some(RED),
+        Level::Warning => Some(YELLOW),
+        Level::Normal => None,
+    }
+}
+
+// Wired links that are up, followed by "VPN" while any tunnel is up.
+fn format_wired(ifaces: &[InterfaceStatus]) -> String {
+    let mut parts: Vec<String> = ifaces.iter()
+        .filter(|i| i.up && i.kind == InterfaceKind::Wired)
+        .map(|i| {
+            let speed = i.speed.map(|s| format!(" {}Mb/s", s)).unwrap_or("".to_owned());
+            let ip = i.addresses.primary().map(|a| format!(" - {}", a)).unwrap_or("".to_owned());
+            format!("{}{}{}", i.name, speed, ip)
+        })
+        .collect();
+
+    if ifaces.iter().any(|i| i.up && i.kind == InterfaceKind::Tunnel) {
+        parts.push("VPN".to_owned());
+    }
+
+    parts.join("  ")
+}
+
+// Average frequency across cores, then the profile or failing that the
+// governor, e.g. "1.9GHz balanced".
+fn format_cpufreq(info: &CpuFreqInfo) -> String {
+    let mut parts = Vec::new();
+
+    if !info.frequencies.is_empty() {
+        let sum: u32 = info.frequencies.iter().sum();
+        let mhz = sum as f64 / info.frequencies.len() as f64;
+        parts.push(format!("{:.1}GHz", mhz / 1000.));
+    }
+
+    if let Some(mode) = info.profile.as_ref().or(info.governor.as_ref()) {
+        parts.push(mode.clone());
+    }
+
+    parts.join(" ")
+}
+
+// "3d 4h", "4h 12m" or "12m".
+fn format_uptime(uptime: Duration) -> String {
+    let mins = uptime.as_secs() / 60;
+    let (days, hours, mins) = (mins / (24 * 60), mins / 60 % 24, mins % 60);
+
+    if days > 0 {
+        format!("{}d {}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, mins)
+    } else {
+        format!("{}m", mins)
+    }
+}
+
+fn format_rate(bytes_per_sec: f64) -> String {
+    format!("{}/s", format_bytes(bytes_per_sec as u64))
+}
+
+fn format_bytes(bytes: u64) -> String {
+    let units = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024. && unit < units.len() - 1 {
+        value /= 1024.;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{:.0} {}", value, units[unit])
+    } else {
+        format!("{:.1} {}", value, units[unit])
+    }
+}
@@ -1,7 +1,8 @@
 extern crate r3bar;
 extern crate i3ipc;
 
-use r3bar::r3ipc::{R3Msg};
+use r3bar::message::Screenshot;
+use r3bar::r3ipc::{self, R3Msg};
 use std::env;
 
 fn help() {
@@ -9,7 +10,12 @@ fn help() {
 msgtype <integer>
     msgtype number - see r3ipc documentation.
 payload [string]
-    msg arguments if any.");
+    msg arguments if any. msgtype 22 (unpark) takes a sensor or
+    --command name to wake only that one.
+
+screenshot <path> [output]
+    save the bar on output, or every bar, as a PNG.
+    {{output}} in the path is replaced by the bar's output.");
 }
 
 fn main() {
@@ -22,6 +28,14 @@ fn main() {
             help();
         },
 
+        l if args[1] == "screenshot" => {
+            match l {
+                3 => screenshot(&args[2], None),
+                4 => screenshot(&args[2], Some(&args[3])),
+                _ => help(),
+            }
+        },
+
         l @ 2...3 => {
 
             let cmd = &args[1];
@@ -46,6 +60,21 @@ fn main() {
     }
 }
 
+fn screenshot(path: &str, output: Option<&String>) {
+    // r3bar's working directory is likely not ours.
+    let path = match env::current_dir() {
+        Ok(dir) => dir.join(path),
+        Err(_) => path.into(),
+    };
+
+    let shot = Screenshot {
+        path: path.to_string_lossy().into_owned(),
+        output: output.cloned(),
+    };
+
+    send(r3ipc::SCREENSHOT, &shot.to_json().to_string());
+}
+
 fn send(msgtype: u32, payload: &str) {
     match R3Msg::new(None).unwrap().send_msg(msgtype, payload) {
         Ok(i3ipc::reply::Command{outcomes}) => println!("{:?}", outcomes),
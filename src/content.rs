@@ -0,0 +1,74 @@
+use conrod::Color;
+use i3bar::{self, Block};
+use super::Orientation;
+
+// What a widget shows, apart from how it's drawn, so the same state can go
+// to the conrod bar, i3bar or a terminal.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Content {
+    pub name: String,
+    pub instance: Option<String>,
+    pub text: String,
+    pub short_text: Option<String>,
+    pub color: Option<Color>,
+    pub urgent: bool,
+    pub orientation: Orientation,
+}
+
+impl Content {
+    pub fn new(name: &str, text: String) -> Self {
+        Content {
+            name: name.to_owned(),
+            instance: None,
+            text: text,
+            short_text: None,
+            color: None,
+            urgent: false,
+            orientation: Orientation::Right,
+        }
+    }
+
+    pub fn with_instance(mut self, instance: Option<String>) -> Self {
+        self.instance = instance;
+        self
+    }
+
+    pub fn with_color(mut self, color: Option<Color>) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn with_urgent(mut self, urgent: bool) -> Self {
+        self.urgent = urgent;
+        self
+    }
+
+    pub fn with_orientation(mut self, o: Orientation) -> Self {
+        self.orientation = o;
+        self
+    }
+
+    // Blocks from a status line producer keep their name, instance and
+    // colours; alignment and widths are i3bar's business.
+    pub fn from_block(block: &Block) -> Self {
+        Content {
+            name: block.name.clone().unwrap_or("".to_owned()),
+            instance: block.instance.clone(),
+            text: block.full_text.clone(),
+            short_text: block.short_text.clone(),
+            color: block.color.as_ref().and_then(|c| i3bar::parse_color(c)),
+            urgent: block.urgent,
+            orientation: Orientation::Right,
+        }
+    }
+
+    pub fn to_block(&self) -> Block {
+        let mut block = Block::new(&self.text);
+        block.name = Some(self.name.clone());
+        block.instance = self.instance.clone();
+        block.short_text = self.short_text.clone();
+        block.color = self.color.map(i3bar::format_color);
+        block.urgent = self.urgent;
+        block
+    }
+}
@@ -0,0 +1,71 @@
+use conrod::position::Place;
+use conrod::{self, widget, Color, Colorable, Positionable, Widget};
+use super::{Gauge, NoEvent};
+
+// A row of vertical bars, one per value, filled from the bottom of the slot
+// in proportion to a percentage.
+pub struct Bars {
+    ids: Vec<conrod::widget::Id>,
+    bar_width: f64,
+    gap: f64,
+    color: Color,
+}
+
+impl Bars {
+    pub fn new(max_bars: usize,
+               bar_width: f64,
+               color: Color,
+               mut id_generator: conrod::widget::id::Generator)
+               -> Bars {
+        let mut ids = Vec::new();
+
+        for _ in 0..max_bars {
+            ids.push(id_generator.next());
+        }
+
+        Bars {
+            ids: ids,
+            bar_width: bar_width,
+            gap: 2.,
+            color: color,
+        }
+    }
+
+}
+
+impl Gauge for Bars {
+    // percentages, one bar each.
+    type Input = Vec<f64>;
+    type Event = NoEvent;
+
+    fn render(&self,
+              percents: Vec<f64>,
+              bar_id: conrod::widget::Id,
+              mut ui_widgets: &mut conrod::UiCell) -> Vec<NoEvent> {
+
+        let height = match ui_widgets.h_of(bar_id) {
+            Some(h) => h,
+            None => return Vec::new(),
+        };
+
+        // zip so we draw min(len(ids), len(percents)) bars
+        for (i, (&id, &percent)) in self.ids.iter().zip(&percents).enumerate() {
+            let fill = percent.max(0.).min(100.) / 100.;
+
+            // conrod won't draw a zero height rectangle, keep a sliver.
+            let h = (height * fill).max(1.);
+
+            widget::Rectangle::fill([self.bar_width, h])
+                .x_place_on(bar_id, Place::Start(Some(i as f64 * (self.bar_width + self.gap))))
+                .align_bottom_of(bar_id)
+                .color(self.color)
+                .set(id, &mut ui_widgets);
+        }
+
+        Vec::new()
+    }
+
+    fn ids(&self) -> Vec<conrod::widget::Id> {
+        self.ids.clone()
+    }
+}
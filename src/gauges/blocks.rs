@@ -0,0 +1,175 @@
+use bar::UiLoop;
+use conrod::{self, color, text, widget, Color, Colorable, Positionable, Widget};
+use i3bar::{self, Align, Block, ClickEvent, MinWidth};
+use layout::{Layout, Orientation};
+use std::sync::Arc;
+use super::Gauge;
+
+// One i3bar block as printed by a status line producer, drawn in a slot of
+// its own. The cell is at least `min_width` wide with the text aligned
+// inside it. Without a block nothing is drawn and the slot shrinks away.
+pub struct StatusBlock {
+    cell_id: conrod::widget::Id,
+    text_id: conrod::widget::Id,
+    urgent_color: Color,
+}
+
+impl StatusBlock {
+    pub fn new(urgent_color: Color,
+               mut id_generator: conrod::widget::id::Generator)
+               -> StatusBlock {
+        StatusBlock {
+            cell_id: id_generator.next(),
+            text_id: id_generator.next(),
+            urgent_color: urgent_color,
+        }
+    }
+}
+
+impl Gauge for StatusBlock {
+    type Input = Option<Block>;
+    // clicks on the block, named as the block is.
+    type Event = ClickEvent;
+
+    fn render(&self,
+              block: Option<Block>,
+              slot_id: conrod::widget::Id,
+              mut ui_widgets: &mut conrod::UiCell) -> Vec<ClickEvent> {
+
+        let height = match ui_widgets.h_of(slot_id) {
+            Some(h) => h,
+            None => return Vec::new(),
+        };
+
+        let block = match block {
+            Some(block) => block,
+            None => {
+                // still set both so last frame's block isn't measured.
+                widget::Rectangle::fill([0., 0.])
+                    .mid_left_of(slot_id)
+                    .parent(slot_id)
+                    .color(color::TRANSPARENT)
+                    .set(self.cell_id, &mut ui_widgets);
+                widget::Text::new("")
+                    .mid_left_of(self.cell_id)
+                    .parent(self.cell_id)
+                    .set(self.text_id, &mut ui_widgets);
+                return Vec::new();
+            },
+        };
+
+        let events = i3bar::click_events(
+            block.name.as_ref().map(|n| n.as_str()).unwrap_or(""),
+            block.instance.as_ref().map(|i| i.as_str()),
+            self.cell_id, &[self.cell_id, self.text_id], ui_widgets);
+
+        let text_w = text_width(&block.full_text, ui_widgets);
+        let w = text_w.max(min_width(&block, ui_widgets).unwrap_or(0) as f64);
+
+        let background = if block.urgent {
+            self.urgent_color
+        } else {
+            block.background.as_ref()
+                .and_then(|b| i3bar::parse_color(b))
+                .unwrap_or(color::TRANSPARENT)
+        };
+
+        widget::Rectangle::fill([w, height])
+            .mid_left_of(slot_id)
+            .parent(slot_id)
+            .color(background)
+            .set(self.cell_id, &mut ui_widgets);
+
+        let label = widget::Text::new(&block.full_text)
+            .parent(self.cell_id)
+            .and_then(block.color.as_ref().and_then(|c| i3bar::parse_color(c)),
+                      |t, c| t.color(c));
+        match block.align {
+            Align::Left => label.mid_left_of(self.cell_id),
+            Align::Center => label.middle_of(self.cell_id),
+            Align::Right => label.mid_right_of(self.cell_id),
+        }.set(self.text_id, &mut ui_widgets);
+
+        events
+    }
+
+    fn ids(&self) -> Vec<conrod::widget::Id> {
+        vec![self.cell_id, self.text_id]
+    }
+}
+
+// Binds a slot for each of the first `max_blocks` blocks of a status line,
+// each with `layout` and the block's min_width. Blocks stay in the order
+// the producer printed them on either side of the bar, and slots for
+// blocks the line doesn't have take up no room.
+pub fn bind<B, E>(ui_context: &mut UiLoop,
+                  layout: Layout,
+                  max_blocks: usize,
+                  urgent_color: Color,
+                  blocks: B,
+                  on_click: E)
+    where B: Fn() -> Vec<Block> + Send + Sync + 'static,
+          E: Fn(ClickEvent) + Send + Sync + 'static
+{
+    let blocks = Arc::new(blocks);
+    let on_click = Arc::new(on_click);
+    let orientation = layout.orientation();
+
+    // rights grow inward from the right edge so are bound last block first.
+    let order: Vec<usize> = match orientation {
+        Orientation::Left => (0..max_blocks).collect(),
+        Orientation::Right => (0..max_blocks).rev().collect(),
+    };
+
+    for i in order {
+        let gauge = Arc::new(StatusBlock::new(urgent_color, ui_context.ui.widget_id_generator()));
+        let measured = gauge.clone();
+        let blocks = blocks.clone();
+        let on_click = on_click.clone();
+
+        ui_context.bind_measured(layout, move |slot_id, ui_widgets, update| {
+            let line = blocks();
+            let block = line.get(i).cloned();
+            let minwidth = block.as_ref().and_then(|b| min_width(b, ui_widgets));
+
+            // a left's separator comes after it and a right's before, which
+            // i3bar has as part of the block before.
+            let separator = match orientation {
+                Orientation::Left => block.as_ref().map_or(true, shows_separator),
+                Orientation::Right => i == 0 || line.get(i - 1).map_or(true, shows_separator),
+            };
+
+            for event in gauge.render(block, slot_id, ui_widgets) {
+                on_click(event);
+            }
+
+            update.apply_minwidth(minwidth).apply_separator(separator)
+        }, move |ui| measured.measure(ui).map(|dim| dim[0] as u32));
+    }
+}
+
+fn shows_separator(block: &Block) -> bool {
+    block.separator && block.separator_block_width > 0
+}
+
+// Width in pixels `block` asks to be at least.
+pub fn min_width(block: &Block, ui: &conrod::Ui) -> Option<u32> {
+    match block.min_width {
+        Some(MinWidth::Pixels(px)) => Some(px),
+        Some(MinWidth::Text(ref t)) => Some(text_width(t, ui).ceil() as u32),
+        None => None,
+    }
+}
+
+// Width of `s` in the theme's font, which is what blocks are drawn with.
+fn text_width(s: &str, ui: &conrod::Ui) -> f64 {
+    let font_id = match ui.theme.font_id.or(ui.fonts.ids().next()) {
+        Some(id) => id,
+        None => return 0.,
+    };
+
+    match ui.fonts.get(font_id) {
+        Some(font) => text::line::width(s, font, ui.theme.font_size_medium),
+        None => 0.,
+    }
+}
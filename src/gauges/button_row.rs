@@ -1,5 +1,6 @@
 use conrod::position::Place;
 use conrod::{self, color, widget, Borderable, Colorable, Color, Positionable, Sizeable, Labelable, Widget};
+use super::Gauge;
 
 pub struct ButtonRow {
     ids: Vec<conrod::widget::Id>,
@@ -12,6 +13,12 @@ pub struct ButtonRow {
 type Title = String;
 type BtnId = String;
 
+pub struct ButtonRowInput {
+    pub buttons: Vec<(Title, BtnId, color::Color)>,
+    // shown after the buttons, e.g. the i3 mode.
+    pub label: String,
+}
+
 impl ButtonRow {
     pub fn new(height: u32,
                button_label_color: Color,
@@ -33,12 +40,20 @@ impl ButtonRow {
         }
     }
 
-    pub fn render(&self,
-                  buttons: Vec<(Title, BtnId, color::Color)>,
-                  label: &str,
-                  bar_id: conrod::widget::Id,
-                  mut ui_widgets: &mut conrod::UiCell)
-                  -> Option<String> {
+}
+
+impl Gauge for ButtonRow {
+    type Input = ButtonRowInput;
+    // the id of a button that was clicked.
+    type Event = BtnId;
+
+    fn render(&self,
+              input: ButtonRowInput,
+              bar_id: conrod::widget::Id,
+              mut ui_widgets: &mut conrod::UiCell)
+              -> Vec<BtnId> {
+
+        let ButtonRowInput{buttons, label} = input;
 
         let basic_btn = || {
             widget::Button::new()
@@ -51,7 +66,7 @@ impl ButtonRow {
                 .center_justify_label()
         };
 
-        let mut clicked_button: Option<String> = None;
+        let mut clicked_buttons = Vec::new();
 
         // we have preallocated 9 ids but we only need buttons.len() of them
         let ids = self.ids.split_at(buttons.len()).0;
@@ -67,7 +82,7 @@ impl ButtonRow {
                 .label(&title)
                 .set(button_id, &mut ui_widgets)
                 .was_clicked() {
-                clicked_button = Some(id);
+                clicked_buttons.push(id);
             }
         }
         // and then line subsequent buttons up relative to first button
@@ -78,14 +93,20 @@ impl ButtonRow {
                 .label(&title)
                 .set(button_id, &mut ui_widgets)
                 .was_clicked() {
-                    clicked_button = Some(id);
+                    clicked_buttons.push(id);
                 }
         }
-        widget::Text::new(label)
+        widget::Text::new(&label)
             .x_place_on(bar_id, Place::End(Some(10.)))
             .color(self.label_color)
             .set(self.label_id, &mut ui_widgets);
 
-        clicked_button
+        clicked_buttons
+    }
+
+    fn ids(&self) -> Vec<conrod::widget::Id> {
+        let mut ids = self.ids.clone();
+        ids.push(self.label_id);
+        ids
     }
 }
@@ -1,6 +1,8 @@
 use conrod::position::Place;
 use conrod::widget::{self, Id};
-use conrod::{self, Positionable, Sizeable, UiCell, Widget};
+use conrod::{self, Colorable, Color, Positionable, Sizeable, UiCell, Widget};
+use i3bar::{self, ClickEvent};
+use super::Gauge;
 
 #[derive(Debug, Clone, Copy)]
 pub struct Icon {
@@ -10,9 +12,11 @@ pub struct Icon {
     pub padding: f64,
 }
 
-pub struct Opts<'a> {
+#[derive(Default)]
+pub struct Opts {
     pub maybe_icon: Option<Icon>,
-    pub maybe_text: Option<&'a str>,
+    pub maybe_text: Option<String>,
+    pub maybe_color: Option<Color>,
 }
 
 pub struct IconText {
@@ -28,7 +32,34 @@ impl IconText {
         }
     }
 
-    pub fn render(&self, opts: Opts, bar_id: Id, mut ui_widgets: &mut UiCell) {
+    // Clicks land on whichever widget is under the mouse so check the slot
+    // as well as the icon and text placed on it.
+    pub fn was_clicked(&self, bar_id: Id, ui_widgets: &UiCell) -> bool {
+        [bar_id, self.icon_id, self.text_id].iter()
+            .any(|&id| ui_widgets.widget_input(id).clicks().left().next().is_some())
+    }
+
+    // Total scroll since the last update. conrod flips the wheel delta so
+    // scrolling up is negative.
+    pub fn scrolled(&self, bar_id: Id, ui_widgets: &UiCell) -> f64 {
+        [bar_id, self.icon_id, self.text_id].iter()
+            .map(|&id| ui_widgets.widget_input(id).scrolls().map(|s| s.y).sum::<f64>())
+            .sum()
+    }
+
+    // Clicks and scrolls since the last update in i3bar's terms, for
+    // handing on to block scripts.
+    pub fn click_events(&self, name: &str, bar_id: Id, ui_widgets: &UiCell) -> Vec<ClickEvent> {
+        i3bar::click_events(name, None, bar_id, &[bar_id, self.icon_id, self.text_id], ui_widgets)
+    }
+}
+
+impl Gauge for IconText {
+    type Input = Opts;
+    // unnamed, whoever bound the gauge knows what it is.
+    type Event = ClickEvent;
+
+    fn render(&self, opts: Opts, bar_id: Id, mut ui_widgets: &mut UiCell) -> Vec<ClickEvent> {
 
         if let Some(icon) = opts.maybe_icon {
             widget::Image::new(icon.id)
@@ -37,19 +68,27 @@ impl IconText {
                 .place_on_kid_area(true)
                 .set(self.icon_id, &mut ui_widgets);
 
-            if let Some(text) = opts.maybe_text {
+            if let Some(ref text) = opts.maybe_text {
                 widget::Text::new(text)
                     .parent(bar_id)
                     .place_on_kid_area(true)
                     .x_relative(icon.w + 4.0)
+                    .and_then(opts.maybe_color, |t, c| t.color(c))
                     .set(self.text_id, &mut ui_widgets);
             }
 
-        } else if let Some(text) = opts.maybe_text {
+        } else if let Some(ref text) = opts.maybe_text {
             widget::Text::new(text)
                 .x_place_on(bar_id, Place::Start(None))
                 .place_on_kid_area(true)
+                .and_then(opts.maybe_color, |t, c| t.color(c))
                 .set(self.text_id, &mut ui_widgets);
         }
+
+        self.click_events("", bar_id, ui_widgets)
+    }
+
+    fn ids(&self) -> Vec<Id> {
+        vec![self.icon_id, self.text_id]
     }
 }
@@ -1,4 +1,41 @@
+use conrod::position::{Dimensions, Rect};
+use conrod::widget::Id;
+use conrod::{Ui, UiCell};
+
 pub mod simple_text;
 pub mod button_row;
 pub mod redkitt;
 pub mod icon_text;
+pub mod bars;
+pub mod blocks;
+
+// For gauges that report nothing.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NoEvent {}
+
+// What every gauge has in common, so a bar can be put together without
+// knowing which gauges it holds. Each is created with ids from the ui's
+// generator, then fed its `Input` and drawn into a slot every frame.
+pub trait Gauge {
+    type Input;
+    type Event;
+
+    // Returns what the user did to the gauge since the last frame.
+    fn render(&self, input: Self::Input, slot_id: Id, ui_widgets: &mut UiCell) -> Vec<Self::Event>;
+
+    // The widgets the gauge draws with.
+    fn ids(&self) -> Vec<Id>;
+
+    // Size of what the gauge drew last frame, None before it has drawn.
+    fn measure(&self, ui: &Ui) -> Option<Dimensions> {
+        self.ids().iter()
+            .filter_map(|&id| ui.rect_of(id))
+            .fold(None, |bbox: Option<Rect>, rect| match bbox {
+                Some(bbox) => Some(Rect::from_corners(
+                    [bbox.left().min(rect.left()), bbox.bottom().min(rect.bottom())],
+                    [bbox.right().max(rect.right()), bbox.top().max(rect.top())])),
+                None => Some(rect),
+            })
+            .map(|bbox| bbox.dim())
+    }
+}
@@ -2,9 +2,16 @@ use conrod::color::{Color};
 use conrod::widget::{Id};
 use conrod::{self, Positionable, Sizeable, Widget};
 use std::time::Duration;
+use super::Gauge;
 
 use widgets;
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum KittEvent {
+    // the sweep in progress has finished.
+    Done,
+}
+
 pub struct RedKitt {
     pub kitt_id: conrod::widget::Id,
     color_grad: Vec<Color>,
@@ -26,8 +33,14 @@ impl RedKitt {
             ],
         }
     }
+}
 
-    pub fn render(&self, slot_id: Id, mut ui: &mut conrod::UiCell, dt: Option<Duration>) -> Option<()> {
+impl Gauge for RedKitt {
+    // time since the last frame while sweeping, None when idle.
+    type Input = Option<Duration>;
+    type Event = KittEvent;
+
+    fn render(&self, dt: Option<Duration>, slot_id: Id, mut ui: &mut conrod::UiCell) -> Vec<KittEvent> {
 
         widgets::kitt::Kitt::new(&self.color_grad)
             .wh_of(slot_id)
@@ -35,5 +48,12 @@ impl RedKitt {
             .dt(dt)
             .middle_of(slot_id)
             .set(self.kitt_id, ui)
+            .map(|_| KittEvent::Done)
+            .into_iter()
+            .collect()
+    }
+
+    fn ids(&self) -> Vec<Id> {
+        vec![self.kitt_id]
     }
 }
@@ -1,5 +1,6 @@
 use conrod::position::Place;
 use conrod::{self, widget, Positionable, Widget};
+use super::{Gauge, NoEvent};
 
 pub struct Simple {
     pub text_id: conrod::widget::Id,
@@ -11,10 +12,21 @@ impl Simple {
             text_id: id_generator.next()
         }
     }
+}
+
+impl Gauge for Simple {
+    type Input = String;
+    type Event = NoEvent;
 
-    pub fn render(&self, text: &str, bar_id: conrod::widget::Id, mut ui_widgets: &mut conrod::UiCell) {
-        widget::Text::new(text)
+    fn render(&self, text: String, bar_id: conrod::widget::Id, mut ui_widgets: &mut conrod::UiCell) -> Vec<NoEvent> {
+        widget::Text::new(&text)
             .x_place_on(bar_id, Place::Middle)
             .set(self.text_id, &mut ui_widgets);
+
+        Vec::new()
+    }
+
+    fn ids(&self) -> Vec<conrod::widget::Id> {
+        vec![self.text_id]
     }
 }
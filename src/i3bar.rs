@@ -0,0 +1,426 @@
+// Pieces of the i3bar protocol shared by block scripts and bars.
+// see https://i3wm.org/docs/i3bar-protocol.html
+
+use conrod::color::{self, Color};
+use conrod::input::MouseButton;
+use conrod::position::Point;
+use conrod::widget::Id;
+use conrod::UiCell;
+use error::BarError;
+use libc;
+use serde_json as json;
+use std::fs::File;
+use std::io::{self, Write};
+use std::os::unix::io::FromRawFd;
+
+// A click on a block as i3bar reports it. Coordinates are pixels, `x` and
+// `y` relative to the bar and `relative_x` and `relative_y` to the block.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClickEvent {
+    pub name: String,
+    pub instance: Option<String>,
+    // 1 left, 2 middle, 3 right, 4 and 5 scroll up and down.
+    pub button: u32,
+    // X11 names: "Shift", "Control", "Mod1" (alt) and "Mod4" (super).
+    pub modifiers: Vec<String>,
+    pub x: i32,
+    pub y: i32,
+    pub relative_x: i32,
+    pub relative_y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl ClickEvent {
+    pub fn new(name: &str, button: u32) -> ClickEvent {
+        ClickEvent {
+            name: name.to_owned(),
+            instance: None,
+            button: button,
+            modifiers: Vec::new(),
+            x: 0,
+            y: 0,
+            relative_x: 0,
+            relative_y: 0,
+            width: 0,
+            height: 0,
+        }
+    }
+
+    pub fn to_json(&self) -> json::Value {
+        let mut obj = json::Map::new();
+        obj.insert("name".to_owned(), json::Value::String(self.name.clone()));
+        if let Some(ref instance) = self.instance {
+            obj.insert("instance".to_owned(), json::Value::String(instance.clone()));
+        }
+        obj.insert("button".to_owned(), json::Value::from(self.button));
+        obj.insert("modifiers".to_owned(), json::Value::Array(
+            self.modifiers.iter().map(|m| json::Value::String(m.clone())).collect()));
+        obj.insert("x".to_owned(), json::Value::from(self.x));
+        obj.insert("y".to_owned(), json::Value::from(self.y));
+        obj.insert("relative_x".to_owned(), json::Value::from(self.relative_x));
+        obj.insert("relative_y".to_owned(), json::Value::from(self.relative_y));
+        obj.insert("width".to_owned(), json::Value::from(self.width));
+        obj.insert("height".to_owned(), json::Value::from(self.height));
+        json::Value::Object(obj)
+    }
+
+    pub fn from_json(value: &json::Value) -> Result<ClickEvent, BarError> {
+        let obj = value.as_object()
+            .ok_or(BarError::Bar("click event not an object".to_owned()))?;
+
+        let int = |key: &str| obj.get(key).and_then(|v| v.as_i64()).unwrap_or(0) as i32;
+
+        Ok(ClickEvent {
+            name: obj.get("name").and_then(|v| v.as_str()).unwrap_or("").to_owned(),
+            instance: obj.get("instance").and_then(|v| v.as_str()).map(|s| s.to_owned()),
+            button: obj.get("button").and_then(|v| v.as_u64())
+                .ok_or(BarError::Bar("click event missing button".to_owned()))? as u32,
+            modifiers: obj.get("modifiers").and_then(|v| v.as_array())
+                .map(|ms| ms.iter().filter_map(|m| m.as_str()).map(|m| m.to_owned()).collect())
+                .unwrap_or(Vec::new()),
+            x: int("x"),
+            y: int("y"),
+            relative_x: int("relative_x"),
+            relative_y: int("relative_y"),
+            width: int("width"),
+            height: int("height"),
+        })
+    }
+}
+
+// Clicks and scrolls on any of `ids` since the last update, positioned
+// within the widget `rect_id`. See `IconText::scrolled` for the direction
+// of scrolling.
+pub fn click_events(name: &str,
+                    instance: Option<&str>,
+                    rect_id: Id,
+                    ids: &[Id],
+                    ui_widgets: &UiCell) -> Vec<ClickEvent> {
+    let rect = match ui_widgets.rect_of(rect_id) {
+        Some(rect) => rect,
+        None => return Vec::new(),
+    };
+
+    // conrod measures from the middle of the window with y going up,
+    // i3bar from the top left corner.
+    let (win_w, win_h) = (ui_widgets.win_w, ui_widgets.win_h);
+    let event = |button: u32, id: Id, xy: Point, modifiers: u8| {
+        let origin = ui_widgets.xy_of(id).unwrap_or([rect.x(), rect.y()]);
+        let (x, y) = (origin[0] + xy[0], origin[1] + xy[1]);
+        let mut event = ClickEvent::new(name, button);
+        event.instance = instance.map(|i| i.to_owned());
+        event.modifiers = modifier_names(modifiers);
+        event.x = (x + win_w / 2.) as i32;
+        event.y = (win_h / 2. - y) as i32;
+        event.relative_x = (x - rect.left()) as i32;
+        event.relative_y = (rect.top() - y) as i32;
+        event.width = rect.w() as i32;
+        event.height = rect.h() as i32;
+        event
+    };
+
+    let mut events = Vec::new();
+    for &id in ids {
+        let input = ui_widgets.widget_input(id);
+
+        for click in input.clicks() {
+            let button = match click.button {
+                MouseButton::Left => 1,
+                MouseButton::Middle => 2,
+                MouseButton::Right => 3,
+                _ => continue,
+            };
+            events.push(event(button, id, click.xy, click.modifiers.bits()));
+        }
+
+        for scroll in input.scrolls() {
+            let button = if scroll.y < 0. { 4 } else { 5 };
+            let xy = input.mouse().map(|m| m.rel_xy()).unwrap_or([0., 0.]);
+            events.push(event(button, id, xy, scroll.modifiers.bits()));
+        }
+    }
+
+    events
+}
+
+// conrod's modifier flags are CTRL, SHIFT, ALT and GUI in that bit order.
+fn modifier_names(bits: u8) -> Vec<String> {
+    [(2, "Shift"), (1, "Control"), (4, "Mod1"), (8, "Mod4")].iter()
+        .filter(|&&(bit, _)| bits & bit != 0)
+        .map(|&(_, name)| name.to_owned())
+        .collect()
+}
+
+// "#RRGGBB" or "#RRGGBBAA".
+pub fn parse_color(hex: &str) -> Option<Color> {
+    // only ASCII hex digits, so slicing by byte stays on char boundaries.
+    if !hex.starts_with('#') || (hex.len() != 7 && hex.len() != 9) ||
+        !hex[1..].bytes().all(|b| (b as char).is_digit(16)) {
+        return None;
+    }
+
+    let byte = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    let alpha = if hex.len() == 9 { byte(7) } else { Some(255) };
+    match (byte(1), byte(3), byte(5), alpha) {
+        (Some(r), Some(g), Some(b), Some(a)) => {
+            Some(color::rgba_bytes(r, g, b, a as f32 / 255.))
+        }
+        _ => None,
+    }
+}
+
+pub fn format_color(c: Color) -> String {
+    let color::Rgba(r, g, b, _) = c.to_rgb();
+    let byte = |v: f32| (v * 255.).round() as u8;
+    format!("#{:02X}{:02X}{:02X}", byte(r), byte(g), byte(b))
+}
+
+// The first line a producer prints.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Header {
+    pub version: u32,
+    pub click_events: bool,
+}
+
+impl Header {
+    pub fn new() -> Header {
+        Header{version: 1, click_events: false}
+    }
+
+    pub fn from_json(value: &json::Value) -> Result<Header, BarError> {
+        let obj = value.as_object()
+            .ok_or(BarError::Bar("i3bar header not an object".to_owned()))?;
+
+        Ok(Header {
+            version: obj.get("version").and_then(|v| v.as_u64())
+                .ok_or(BarError::Bar("i3bar header missing version".to_owned()))? as u32,
+            click_events: obj.get("click_events").and_then(|v| v.as_bool()).unwrap_or(false),
+        })
+    }
+
+    pub fn to_json(&self) -> json::Value {
+        let mut obj = json::Map::new();
+        obj.insert("version".to_owned(), json::Value::from(self.version));
+        if self.click_events {
+            obj.insert("click_events".to_owned(), json::Value::Bool(true));
+        }
+        json::Value::Object(obj)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+// A block is at least as wide as `Pixels`, or as the given text would be.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MinWidth {
+    Pixels(u32),
+    Text(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Block {
+    pub full_text: String,
+    pub short_text: Option<String>,
+    pub color: Option<String>,
+    pub background: Option<String>,
+    pub urgent: bool,
+    pub min_width: Option<MinWidth>,
+    pub align: Align,
+    pub name: Option<String>,
+    pub instance: Option<String>,
+    pub separator: bool,
+    pub separator_block_width: u32,
+}
+
+impl Block {
+    pub fn new(full_text: &str) -> Block {
+        Block {
+            full_text: full_text.to_owned(),
+            short_text: None,
+            color: None,
+            background: None,
+            urgent: false,
+            min_width: None,
+            align: Align::Left,
+            name: None,
+            instance: None,
+            separator: true,
+            separator_block_width: 9,
+        }
+    }
+
+    pub fn from_json(value: &json::Value) -> Result<Block, BarError> {
+        let obj = value.as_object()
+            .ok_or(BarError::Bar("i3bar block not an object".to_owned()))?;
+
+        let string = |key: &str| obj.get(key).and_then(|v| v.as_str()).map(|s| s.to_owned());
+
+        let full_text = string("full_text")
+            .ok_or(BarError::Bar("i3bar block missing full_text".to_owned()))?;
+
+        let mut block = Block::new(&full_text);
+        block.short_text = string("short_text");
+        block.color = string("color");
+        block.background = string("background");
+        block.urgent = obj.get("urgent").and_then(|v| v.as_bool()).unwrap_or(false);
+        block.min_width = match obj.get("min_width") {
+            Some(&json::Value::String(ref text)) => Some(MinWidth::Text(text.clone())),
+            Some(v) => v.as_u64().map(|px| MinWidth::Pixels(px as u32)),
+            None => None,
+        };
+        block.align = match string("align").as_ref().map(|a| a.as_str()) {
+            Some("center") => Align::Center,
+            Some("right") => Align::Right,
+            _ => Align::Left,
+        };
+        block.name = string("name");
+        block.instance = string("instance");
+        block.separator = obj.get("separator").and_then(|v| v.as_bool()).unwrap_or(true);
+        block.separator_block_width = obj.get("separator_block_width")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(9) as u32;
+
+        Ok(block)
+    }
+
+    // Only fields that differ from i3bar's defaults are written.
+    pub fn to_json(&self) -> json::Value {
+        let mut obj = json::Map::new();
+        let string = |s: &str| json::Value::String(s.to_owned());
+
+        obj.insert("full_text".to_owned(), string(&self.full_text));
+        if let Some(ref short_text) = self.short_text {
+            obj.insert("short_text".to_owned(), string(short_text));
+        }
+        if let Some(ref color) = self.color {
+            obj.insert("color".to_owned(), string(color));
+        }
+        if let Some(ref background) = self.background {
+            obj.insert("background".to_owned(), string(background));
+        }
+        if self.urgent {
+            obj.insert("urgent".to_owned(), json::Value::Bool(true));
+        }
+        match self.min_width {
+            Some(MinWidth::Pixels(px)) => {
+                obj.insert("min_width".to_owned(), json::Value::from(px));
+            }
+            Some(MinWidth::Text(ref text)) => {
+                obj.insert("min_width".to_owned(), string(text));
+            }
+            None => (),
+        }
+        match self.align {
+            Align::Center => { obj.insert("align".to_owned(), string("center")); }
+            Align::Right => { obj.insert("align".to_owned(), string("right")); }
+            Align::Left => (),
+        }
+        if let Some(ref name) = self.name {
+            obj.insert("name".to_owned(), string(name));
+        }
+        if let Some(ref instance) = self.instance {
+            obj.insert("instance".to_owned(), string(instance));
+        }
+        if !self.separator {
+            obj.insert("separator".to_owned(), json::Value::Bool(false));
+        }
+        if self.separator_block_width != 9 {
+            obj.insert("separator_block_width".to_owned(),
+                       json::Value::from(self.separator_block_width));
+        }
+        json::Value::Object(obj)
+    }
+}
+
+// After the header producers print an endless JSON array with one status
+// line per line of output:
+//
+//   [
+//   [{"full_text":"E: down"},{"full_text":"W: up"}],
+//   [{"full_text":"E: down"},{"full_text":"W: down"}],
+//
+// Some put the comma at the start of the line instead. Returns None for
+// lines that carry no status, like the opening bracket.
+pub fn parse_status_line(line: &str) -> Result<Option<Vec<Block>>, BarError> {
+    let line = line.trim().trim_left_matches(',').trim_right_matches(',').trim();
+    if line.is_empty() || line == "[" {
+        return Ok(None);
+    }
+
+    let value: json::Value = json::from_str(line)?;
+    let blocks = value.as_array()
+        .ok_or(BarError::Bar("i3bar status line not an array".to_owned()))?
+        .iter()
+        .map(Block::from_json)
+        .collect::<Result<Vec<Block>, BarError>>()?;
+
+    Ok(Some(blocks))
+}
+
+// The same for click events i3bar writes to a producer's stdin.
+pub fn parse_click_line(line: &str) -> Result<Option<ClickEvent>, BarError> {
+    let line = line.trim().trim_left_matches(',').trim_right_matches(',').trim();
+    if line.is_empty() || line == "[" {
+        return Ok(None);
+    }
+
+    let value: json::Value = json::from_str(line)?;
+    ClickEvent::from_json(&value).map(Some)
+}
+
+// Sensors report errors with println! which would corrupt the protocol.
+// Returns a handle on the real stdout for a `Writer` and sends anything
+// else printed to stderr.
+pub fn take_stdout() -> io::Result<File> {
+    unsafe {
+        let fd = libc::dup(libc::STDOUT_FILENO);
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        if libc::dup2(libc::STDERR_FILENO, libc::STDOUT_FILENO) < 0 {
+            let err = io::Error::last_os_error();
+            libc::close(fd);
+            return Err(err);
+        }
+        Ok(File::from_raw_fd(fd))
+    }
+}
+
+// Writes the producer side of the protocol: a header then one status line
+// per call to `write`.
+pub struct Writer<W: Write> {
+    out: W,
+    first: bool,
+}
+
+impl<W: Write> Writer<W> {
+    pub fn new(mut out: W, header: &Header) -> io::Result<Writer<W>> {
+        writeln!(out, "{}", header.to_json())?;
+        writeln!(out, "[")?;
+        out.flush()?;
+
+        Ok(Writer{out: out, first: true})
+    }
+
+    pub fn write(&mut self, blocks: &[Block]) -> io::Result<()> {
+        let line = json::Value::Array(blocks.iter().map(|b| b.to_json()).collect());
+
+        if self.first {
+            writeln!(self.out, "{}", line)?;
+            self.first = false;
+        } else {
+            writeln!(self.out, ",{}", line)?;
+        }
+        self.out.flush()
+    }
+
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+}
@@ -0,0 +1,192 @@
+// Width negotiation for the bar's slots, kept apart from conrod so it can
+// be tested on its own. Every frame each slot's width is worked out from
+// its `Layout`, what it drew last frame and its width the frame before,
+// then the slots are placed left to right with a spacer taking up
+// whatever room is left in the middle.
+
+use std;
+
+// Width of a slot with no width set before it has drawn anything.
+pub const DEFAULT_WIDTH: u32 = 200;
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum Orientation {
+    Left,
+    Right,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Layout {
+    width: Option<u32>,
+    minwidth: Option<u32>,
+    maxwidth: Option<u32>,
+    smoothwidth: Option<u32>,
+    orientation: Orientation,
+}
+
+impl Layout {
+    pub fn new() -> Self {
+        Layout{
+            width: None,
+            minwidth: None,
+            maxwidth: None,
+            smoothwidth: Some(4),
+            orientation: Orientation::Right,
+        }
+    }
+
+    pub fn with_width(mut self, width: Option<u32>) -> Self {
+        self.width = width;
+        self
+    }
+
+    pub fn with_minwidth(mut self, width: Option<u32>) -> Self {
+        self.minwidth = width;
+        self
+    }
+
+    pub fn with_maxwidth(mut self, width: Option<u32>) -> Self {
+        self.maxwidth = width;
+        self
+    }
+
+    // Changes in width up to `delta` only ever grow the slot, which stops
+    // it jittering as its contents change, e.g. a clock's digits.
+    pub fn with_smoothwidth(mut self, delta: Option<u32>) -> Self {
+        self.smoothwidth = delta;
+        self
+    }
+
+    pub fn with_orientation(mut self, o: Orientation) -> Self {
+        self.orientation = o;
+        self
+    }
+
+    pub fn orientation(&self) -> Orientation {
+        self.orientation
+    }
+
+    // Dragging a slot with a set width by `delta` pixels. Slots sized by
+    // their contents can't be dragged.
+    pub fn resized(self, delta: f64) -> Self {
+        match self.width {
+            Some(w) => self.with_width(Some((w as f64 + delta).max(0.) as u32)),
+            None => self,
+        }
+    }
+}
+
+// Both hold an index into the layouts given to `arrange`, for a separator
+// the widget it belongs to.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum Slot {
+    Widget(usize),
+    Separator(usize),
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct Arrangement {
+    pub slots: Vec<Slot>,
+    // the spacer goes before `slots[spacer]`.
+    pub spacer: usize,
+}
+
+// Orders widgets so that Lefts grow inward from the left edge in the order
+// they were bound and Rights grow inward from the right edge. Each widget
+// has a separator on its inner side, after a left and before a right, so
+// none on the ends and a double one in the middle either side of the
+// spacer:
+//
+//     [l1 s l2 s <spacer> s r2 s r1]
+pub fn arrange(orientations: &[Orientation]) -> Arrangement {
+    let mut slots = Vec::new();
+    let mut left_i = 0;
+
+    for (i, &o) in orientations.iter().enumerate() {
+        match o {
+            Orientation::Left => {
+                slots.insert(left_i, Slot::Widget(i));
+                slots.insert(left_i + 1, Slot::Separator(i));
+                left_i += 2;
+            },
+            Orientation::Right => {
+                slots.insert(left_i, Slot::Separator(i));
+                slots.insert(left_i + 1, Slot::Widget(i));
+            },
+        }
+    }
+
+    Arrangement {
+        slots: slots,
+        spacer: left_i,
+    }
+}
+
+// A slot's width this frame. `measured` is the width of what it drew last
+// frame, if anything, and `previous` the width it was given.
+pub fn width(layout: &Layout, measured: Option<u32>, previous: u32) -> u32 {
+    // TODO also consider effects of padding & margins?
+    let mut w = layout.width.unwrap_or(measured.unwrap_or(DEFAULT_WIDTH));
+
+    if let Some(min_w) = layout.minwidth {
+        w = std::cmp::max(w, min_w);
+    }
+
+    if let Some(max_w) = layout.maxwidth {
+        w = std::cmp::min(w, max_w);
+    }
+
+    // If the difference between values is less than or equal to the
+    // smoothing delta keep the greater of the values.
+    if let Some(dw) = layout.smoothwidth {
+        let diff = w as i64 - previous as i64;
+        if diff.abs() <= dw as i64 {
+            w = std::cmp::max(w, previous);
+        }
+    }
+
+    w
+}
+
+// Separators take up no room when the widget they belong to is empty or
+// goes without one, so a widget with nothing to show leaves no trace.
+// `widths` are per slot and `separators` per widget.
+pub fn hide_separators(slots: &[Slot], widths: &mut [u32], separators: &[bool]) {
+    for i in 0..slots.len() {
+        if let Slot::Separator(owner) = slots[i] {
+            let owner_w = slots.iter()
+                .position(|&s| s == Slot::Widget(owner))
+                .map_or(0, |j| widths[j]);
+
+            if owner_w == 0 || !separators.get(owner).map_or(true, |&shown| shown) {
+                widths[i] = 0;
+            }
+        }
+    }
+}
+
+// Where a slot sits, in pixels from the left of the bar.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct Rect {
+    pub x: u32,
+    pub w: u32,
+}
+
+// Lays out slots of the given widths with a spacer inserted at `spacer`
+// taking up the rest of the bar, so the result is one longer than
+// `widths`. When the slots don't fit the spacer is empty and they run off
+// the right hand end.
+pub fn place(widths: &[u32], spacer: usize, bar_width: u32) -> Vec<Rect> {
+    let total = widths.iter().fold(0, |sum, &w| sum + w);
+    let spacer_w = bar_width.saturating_sub(total);
+
+    let mut all = widths.to_vec();
+    all.insert(spacer, spacer_w);
+
+    let mut x = 0;
+    all.iter().map(|&w| {
+        let rect = Rect{x: x, w: w};
+        x += w;
+        rect
+    }).collect()
+}
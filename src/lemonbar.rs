@@ -0,0 +1,77 @@
+// Output for lemonbar, https://github.com/LemonBoy/bar
+//
+// Every block is wrapped in click actions naming it, which lemonbar prints
+// on its stdout when clicked. Each action uses one of lemonbar's clickable
+// areas so start it with enough, e.g. `lemonbar -a 100`.
+
+use content::Content;
+use error::BarError;
+use i3bar::{self, ClickEvent};
+use super::Orientation;
+
+const SEPARATOR: &'static str = " | ";
+
+// Left and right click, then the scroll wheel.
+pub const BUTTONS: [u32; 4] = [1, 3, 4, 5];
+
+// One line of lemonbar markup. Left oriented content goes to `%{l}`, the
+// rest to `%{r}`, and empty content is skipped.
+pub fn render(contents: &[Content]) -> String {
+    let side = |o: Orientation| contents.iter()
+        .filter(|c| c.orientation == o && !c.text.is_empty())
+        .map(render_one)
+        .collect::<Vec<_>>()
+        .join(SEPARATOR);
+
+    format!("%{{l}}{}%{{r}}{}", side(Orientation::Left), side(Orientation::Right))
+}
+
+fn render_one(content: &Content) -> String {
+    let mut out = content.text.replace("%", "%%");
+
+    if content.urgent {
+        out = format!("%{{R}}{}%{{R}}", out);
+    }
+
+    if let Some(color) = content.color {
+        out = format!("%{{F{}}}{}%{{F-}}", i3bar::format_color(color), out);
+    }
+
+    for &button in BUTTONS.iter() {
+        out = format!("%{{A{}:{}:}}{}%{{A}}", button, action(content, button), out);
+    }
+
+    out
+}
+
+// "<button>:<name>[:<instance>]" with the colons escaped for lemonbar.
+fn action(content: &Content, button: u32) -> String {
+    let mut action = format!("{}:{}", button, content.name);
+    if let Some(ref instance) = content.instance {
+        action.push(':');
+        action.push_str(instance);
+    }
+    action.replace(":", "\\:")
+}
+
+// A line lemonbar printed for a click. The instance is everything after
+// the name so it may contain colons itself.
+pub fn parse_action(line: &str) -> Result<Option<ClickEvent>, BarError> {
+    let line = line.trim().replace("\\:", ":");
+    if line.is_empty() {
+        return Ok(None);
+    }
+
+    let mut split = line.splitn(3, ':');
+    let button = split.next().and_then(|b| b.parse::<u32>().ok());
+    let name = split.next();
+
+    match (button, name) {
+        (Some(button), Some(name)) => {
+            let mut event = ClickEvent::new(name, button);
+            event.instance = split.next().map(|i| i.to_owned());
+            Ok(Some(event))
+        }
+        _ => Err(BarError::Bar(format!("unknown lemonbar action '{}'", line))),
+    }
+}
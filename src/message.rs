@@ -1,8 +1,22 @@
 use error::BarError;
+use i3bar::Block;
+use sensors::backlight::BacklightInfo;
+use sensors::command::CommandOutput;
+use sensors::cpu::CpuUsage;
+use sensors::cpufreq::CpuFreqInfo;
+use sensors::loadavg::LoadInfo;
 use i3ipc::reply::Workspace;
+use sensors::diskio::DeviceIo;
+use sensors::diskusage::MountUsage;
+use sensors::memory::MemInfo;
+use sensors::microphone::MicStatus;
+use sensors::netspeed::InterfaceRate;
+use sensors::thermal::ThermalInfo;
 use sensors::wifi::WifiStatus;
+use sensors::wired::InterfaceStatus;
 use serde_json as json;
 use std::str::FromStr;
+use std::sync::mpsc;
 
 
 #[derive(Debug)]
@@ -12,13 +26,28 @@ pub enum Message {
     Exit(i32),
     I3Mode(String),
     Time(String),
-    Unpark,
-    DiskUsage(String),
+    // Wake the named sensor, or every sensor.
+    Unpark(Option<String>),
+    DiskUsage(Vec<MountUsage>),
+    DiskIo(Vec<DeviceIo>),
     Volume(String),
+    Microphone(MicStatus),
+    Backlight(BacklightInfo),
     Webpack(WebpackInfo),
     Wifi(WifiStatus),
+    Wired(Vec<InterfaceStatus>),
+    NetSpeed(Vec<InterfaceRate>),
+    Cpu(CpuUsage),
+    CpuFreq(CpuFreqInfo),
+    LoadAvg(LoadInfo),
+    Memory(MemInfo),
+    Thermal(ThermalInfo),
     Workspaces(Vec<Workspace>),
     Ticker(String),
+    Command(String, CommandOutput),
+    StatusLine(String, Vec<Block>),
+    // answered with how it went, for the r3ipc reply.
+    Screenshot(Screenshot, mpsc::Sender<Result<(), String>>),
 }
 
 #[derive(Debug)]
@@ -49,3 +78,68 @@ impl FromStr for WebpackInfo {
         })
     }
 }
+
+// Where to save a PNG of the bar on `output`, or of every bar when there's
+// no output. "{output}" in the path is replaced with the bar's output.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Screenshot {
+    pub path: String,
+    pub output: Option<String>,
+}
+
+// Data for `r3ipc::SCREENSHOT`, e.g. {"path": "/tmp/bar.png", "output": "eDP1"}.
+impl FromStr for Screenshot {
+    type Err = BarError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let data: json::Value = json::from_str(s)?;
+        let obj = data.as_object()
+            .ok_or(BarError::Bar(format!("screenshot json not object")))?;
+
+        let path = obj.get("path").and_then(|p| p.as_str())
+            .ok_or(BarError::Bar(format!("path field missing String")))?;
+
+        Ok(Screenshot {
+            path: path.to_owned(),
+            output: obj.get("output").and_then(|o| o.as_str()).map(|o| o.to_owned()),
+        })
+    }
+}
+
+// What one bar did with a screenshot, None when it's for another output.
+pub type ScreenshotResult = Option<Result<(), String>>;
+
+impl Screenshot {
+    // Sums up what each bar did with the screenshot.
+    pub fn outcome(&self, results: &[ScreenshotResult]) -> Result<(), String> {
+        let errors: Vec<&str> = results.iter()
+            .filter_map(|r| match *r {
+                Some(Err(ref e)) => Some(e.as_str()),
+                _ => None,
+            })
+            .collect();
+
+        if !errors.is_empty() {
+            return Err(errors.join("; "));
+        }
+
+        if results.is_empty() {
+            return Err("no bar window to capture".to_owned());
+        }
+
+        match (results.iter().any(|r| r.is_some()), self.output.as_ref()) {
+            (true, _) => Ok(()),
+            (false, Some(output)) => Err(format!("no such output '{}'", output)),
+            (false, None) => Err("no bar window to capture".to_owned()),
+        }
+    }
+
+    pub fn to_json(&self) -> json::Value {
+        let mut obj = json::Map::new();
+        obj.insert("path".to_owned(), json::Value::String(self.path.clone()));
+        if let Some(ref output) = self.output {
+            obj.insert("output".to_owned(), json::Value::String(output.clone()));
+        }
+        json::Value::Object(obj)
+    }
+}
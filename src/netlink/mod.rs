@@ -0,0 +1,360 @@
+// Just enough netlink to talk to the kernel without shelling out.
+// see http://man7.org/linux/man-pages/man7/netlink.7.html
+
+use byteorder::{ByteOrder, NativeEndian, WriteBytesExt};
+use libc;
+use std::collections::HashMap;
+use std::io;
+use std::mem;
+use std::os::unix::io::RawFd;
+
+pub mod nl80211;
+pub mod route;
+
+pub const NETLINK_ROUTE: i32 = 0;
+pub const NETLINK_GENERIC: i32 = 16;
+
+const SOL_NETLINK: i32 = 270;
+const NETLINK_ADD_MEMBERSHIP: i32 = 1;
+
+pub const NLM_F_REQUEST: u16 = 0x1;
+pub const NLM_F_MULTI: u16 = 0x2;
+pub const NLM_F_ACK: u16 = 0x4;
+pub const NLM_F_DUMP: u16 = 0x300;
+
+const NLMSG_ERROR: u16 = 0x2;
+const NLMSG_DONE: u16 = 0x3;
+
+const NLMSG_HDRLEN: usize = 16;
+const NLA_HDRLEN: usize = 4;
+const NLA_TYPE_MASK: u16 = 0x3fff;
+
+// generic netlink controller
+const GENL_ID_CTRL: u16 = 0x10;
+const CTRL_CMD_GETFAMILY: u8 = 3;
+const CTRL_ATTR_FAMILY_ID: u16 = 1;
+const CTRL_ATTR_FAMILY_NAME: u16 = 2;
+const CTRL_ATTR_MCAST_GROUPS: u16 = 7;
+const CTRL_ATTR_MCAST_GRP_NAME: u16 = 1;
+const CTRL_ATTR_MCAST_GRP_ID: u16 = 2;
+
+pub const GENL_HDRLEN: usize = 4;
+
+fn align(len: usize) -> usize {
+    (len + 3) & !3
+}
+
+#[derive(Debug)]
+pub struct Msg {
+    pub kind: u16,
+    pub flags: u16,
+    pub payload: Vec<u8>,
+}
+
+impl Msg {
+    // generic netlink command, valid for messages from a genl family.
+    pub fn genl_cmd(&self) -> Option<u8> {
+        self.payload.get(0).map(|&c| c)
+    }
+
+    // attributes following a header of `hdrlen` bytes.
+    pub fn attrs(&self, hdrlen: usize) -> Attrs {
+        if self.payload.len() < hdrlen {
+            return Attrs::new(&[]);
+        }
+        Attrs::new(&self.payload[hdrlen..])
+    }
+}
+
+// Iterates over (type, payload) pairs of a netlink attribute stream.
+pub struct Attrs<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Attrs<'a> {
+    pub fn new(data: &'a [u8]) -> Attrs<'a> {
+        Attrs{data: data}
+    }
+}
+
+impl<'a> Iterator for Attrs<'a> {
+    type Item = (u16, &'a [u8]);
+
+    fn next(&mut self) -> Option<(u16, &'a [u8])> {
+        if self.data.len() < NLA_HDRLEN {
+            return None;
+        }
+
+        let len = NativeEndian::read_u16(&self.data[0..2]) as usize;
+        let kind = NativeEndian::read_u16(&self.data[2..4]) & NLA_TYPE_MASK;
+        if len < NLA_HDRLEN || len > self.data.len() {
+            return None;
+        }
+
+        let payload = &self.data[NLA_HDRLEN..len];
+        let next = align(len);
+        self.data = if next >= self.data.len() { &[] } else { &self.data[next..] };
+
+        Some((kind, payload))
+    }
+}
+
+pub fn attr_u16(data: &[u8]) -> Option<u16> {
+    if data.len() < 2 { None } else { Some(NativeEndian::read_u16(data)) }
+}
+
+pub fn attr_u32(data: &[u8]) -> Option<u32> {
+    if data.len() < 4 { None } else { Some(NativeEndian::read_u32(data)) }
+}
+
+// strings may or may not be nul terminated depending on the attribute.
+pub fn attr_string(data: &[u8]) -> String {
+    let end = data.iter().position(|&b| b == 0).unwrap_or(data.len());
+    String::from_utf8_lossy(&data[..end]).into_owned()
+}
+
+// Builds a single netlink request. The header length is filled in when the
+// message is finished.
+pub struct MsgBuilder {
+    buf: Vec<u8>,
+}
+
+impl MsgBuilder {
+    pub fn new(kind: u16, flags: u16) -> MsgBuilder {
+        let mut buf = Vec::with_capacity(64);
+        buf.write_u32::<NativeEndian>(0).unwrap();        // length
+        buf.write_u16::<NativeEndian>(kind).unwrap();
+        buf.write_u16::<NativeEndian>(flags).unwrap();
+        buf.write_u32::<NativeEndian>(0).unwrap();        // sequence
+        buf.write_u32::<NativeEndian>(0).unwrap();        // port id
+        MsgBuilder{buf: buf}
+    }
+
+    // append a fixed family header such as genlmsghdr or ifinfomsg.
+    pub fn header(mut self, bytes: &[u8]) -> MsgBuilder {
+        self.buf.extend(bytes);
+        self.pad();
+        self
+    }
+
+    pub fn genl(self, cmd: u8, version: u8) -> MsgBuilder {
+        self.header(&[cmd, version, 0, 0])
+    }
+
+    pub fn attr(mut self, kind: u16, data: &[u8]) -> MsgBuilder {
+        self.buf.write_u16::<NativeEndian>((NLA_HDRLEN + data.len()) as u16).unwrap();
+        self.buf.write_u16::<NativeEndian>(kind).unwrap();
+        self.buf.extend(data);
+        self.pad();
+        self
+    }
+
+    pub fn attr_u32(self, kind: u16, value: u32) -> MsgBuilder {
+        let mut data = [0u8; 4];
+        NativeEndian::write_u32(&mut data, value);
+        self.attr(kind, &data)
+    }
+
+    pub fn attr_str(self, kind: u16, value: &str) -> MsgBuilder {
+        let mut data = value.as_bytes().to_vec();
+        data.push(0);
+        self.attr(kind, &data)
+    }
+
+    fn pad(&mut self) {
+        while self.buf.len() % 4 != 0 {
+            self.buf.push(0);
+        }
+    }
+
+    fn finish(mut self, seq: u32) -> Vec<u8> {
+        let len = self.buf.len() as u32;
+        NativeEndian::write_u32(&mut self.buf[0..4], len);
+        NativeEndian::write_u32(&mut self.buf[8..12], seq);
+        self.buf
+    }
+}
+
+pub struct Socket {
+    fd: RawFd,
+    seq: u32,
+}
+
+impl Socket {
+    pub fn open(protocol: i32) -> io::Result<Socket> {
+        let fd = unsafe {
+            libc::socket(libc::AF_NETLINK, libc::SOCK_RAW | libc::SOCK_CLOEXEC, protocol)
+        };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let socket = Socket{fd: fd, seq: 0};
+
+        let mut addr: libc::sockaddr_nl = unsafe { mem::zeroed() };
+        addr.nl_family = libc::AF_NETLINK as libc::sa_family_t;
+        let r = unsafe {
+            libc::bind(fd,
+                       &addr as *const libc::sockaddr_nl as *const libc::sockaddr,
+                       mem::size_of::<libc::sockaddr_nl>() as libc::socklen_t)
+        };
+        if r < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(socket)
+    }
+
+    pub fn add_membership(&self, group: u32) -> io::Result<()> {
+        let r = unsafe {
+            libc::setsockopt(self.fd, SOL_NETLINK, NETLINK_ADD_MEMBERSHIP,
+                             &group as *const u32 as *const libc::c_void,
+                             mem::size_of::<u32>() as libc::socklen_t)
+        };
+        if r < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    // Send a request and collect every reply up to the end of a dump or the
+    // acknowledgement of a single request.
+    pub fn request(&mut self, msg: MsgBuilder) -> io::Result<Vec<Msg>> {
+        self.seq = self.seq.wrapping_add(1);
+        let bytes = msg.finish(self.seq);
+        let wants_ack = NativeEndian::read_u16(&bytes[6..8]) & NLM_F_ACK != 0;
+
+        let r = unsafe {
+            libc::send(self.fd, bytes.as_ptr() as *const libc::c_void, bytes.len(), 0)
+        };
+        if r < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let mut replies = Vec::new();
+        loop {
+            let mut more = wants_ack;
+            for msg in self.recv()? {
+                match msg.kind {
+                    NLMSG_DONE => return Ok(replies),
+                    NLMSG_ERROR => {
+                        // an error code of 0 is an acknowledgement.
+                        let code = match msg.payload.len() {
+                            l if l >= 4 => NativeEndian::read_i32(&msg.payload[0..4]),
+                            _ => 0,
+                        };
+                        if code != 0 {
+                            return Err(io::Error::from_raw_os_error(-code));
+                        }
+                        return Ok(replies);
+                    }
+                    _ => {
+                        if msg.flags & NLM_F_MULTI != 0 {
+                            more = true;
+                        }
+                        replies.push(msg);
+                    }
+                }
+            }
+            if !more {
+                return Ok(replies);
+            }
+        }
+    }
+
+    // Block until the next datagram and split it into messages.
+    pub fn recv(&self) -> io::Result<Vec<Msg>> {
+        let mut buf = vec![0u8; 32768];
+        let n = loop {
+            let n = unsafe {
+                libc::recv(self.fd, buf.as_mut_ptr() as *mut libc::c_void, buf.len(), 0)
+            };
+            if n >= 0 {
+                break n as usize;
+            }
+            let err = io::Error::last_os_error();
+            if err.kind() != io::ErrorKind::Interrupted {
+                return Err(err);
+            }
+        };
+
+        Ok(parse(&buf[..n]))
+    }
+}
+
+// Split a datagram into its messages, dropping a truncated tail.
+pub fn parse(mut data: &[u8]) -> Vec<Msg> {
+    let mut msgs = Vec::new();
+    while data.len() >= NLMSG_HDRLEN {
+        let len = NativeEndian::read_u32(&data[0..4]) as usize;
+        if len < NLMSG_HDRLEN || len > data.len() {
+            break;
+        }
+
+        msgs.push(Msg{
+            kind: NativeEndian::read_u16(&data[4..6]),
+            flags: NativeEndian::read_u16(&data[6..8]),
+            payload: data[NLMSG_HDRLEN..len].to_vec(),
+        });
+
+        let next = align(len);
+        data = if next >= data.len() { &[] } else { &data[next..] };
+    }
+
+    msgs
+}
+
+impl Drop for Socket {
+    fn drop(&mut self) {
+        unsafe { libc::close(self.fd); }
+    }
+}
+
+// A generic netlink family resolved through the controller.
+#[derive(Debug, Clone)]
+pub struct Family {
+    pub id: u16,
+    pub groups: HashMap<String, u32>,
+}
+
+pub fn resolve_family(socket: &mut Socket, name: &str) -> io::Result<Family> {
+    let req = MsgBuilder::new(GENL_ID_CTRL, NLM_F_REQUEST | NLM_F_ACK)
+        .genl(CTRL_CMD_GETFAMILY, 1)
+        .attr_str(CTRL_ATTR_FAMILY_NAME, name);
+
+    let mut family = None;
+    for msg in socket.request(req)? {
+        let mut id = None;
+        let mut groups = HashMap::new();
+
+        for (kind, data) in msg.attrs(GENL_HDRLEN) {
+            match kind {
+                CTRL_ATTR_FAMILY_ID => id = attr_u16(data),
+                CTRL_ATTR_MCAST_GROUPS => {
+                    for (_, group) in Attrs::new(data) {
+                        let mut gname = None;
+                        let mut gid = None;
+                        for (gkind, gdata) in Attrs::new(group) {
+                            match gkind {
+                                CTRL_ATTR_MCAST_GRP_NAME => gname = Some(attr_string(gdata)),
+                                CTRL_ATTR_MCAST_GRP_ID => gid = attr_u32(gdata),
+                                _ => (),
+                            }
+                        }
+                        if let (Some(n), Some(i)) = (gname, gid) {
+                            groups.insert(n, i);
+                        }
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        if let Some(id) = id {
+            family = Some(Family{id: id, groups: groups});
+        }
+    }
+
+    family.ok_or(io::Error::new(
+        io::ErrorKind::NotFound, format!("netlink family '{}' not found", name)))
+}
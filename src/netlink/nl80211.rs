@@ -0,0 +1,201 @@
+// Query wireless link state from the kernel's nl80211 interface, the same
+// source `iw` reads from.
+// see https://git.kernel.org/pub/scm/linux/kernel/git/torvalds/linux.git/tree/include/uapi/linux/nl80211.h
+
+use netlink::{self, Attrs, Family, Msg, MsgBuilder, Socket};
+use std::io;
+
+const NL80211_CMD_GET_INTERFACE: u8 = 5;
+const NL80211_CMD_GET_STATION: u8 = 17;
+pub const NL80211_CMD_CONNECT: u8 = 46;
+pub const NL80211_CMD_DISCONNECT: u8 = 48;
+
+const NL80211_ATTR_IFINDEX: u16 = 3;
+const NL80211_ATTR_IFNAME: u16 = 4;
+const NL80211_ATTR_STA_INFO: u16 = 21;
+const NL80211_ATTR_WIPHY_FREQ: u16 = 38;
+const NL80211_ATTR_SSID: u16 = 52;
+
+const NL80211_STA_INFO_SIGNAL: u16 = 7;
+const NL80211_STA_INFO_TX_BITRATE: u16 = 8;
+
+const NL80211_RATE_INFO_BITRATE: u16 = 1;
+const NL80211_RATE_INFO_BITRATE32: u16 = 5;
+
+const MLME_GROUP: &'static str = "mlme";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Interface {
+    pub index: u32,
+    pub name: String,
+}
+
+// The current association of a wireless interface. Everything is None
+// while disconnected.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Link {
+    pub ssid: Option<String>,
+    // dBm
+    pub signal: Option<f64>,
+    // Mbit/s
+    pub bitrate: Option<f64>,
+    // MHz
+    pub frequency: Option<u32>,
+}
+
+pub struct Nl80211 {
+    socket: Socket,
+    family: Family,
+}
+
+impl Nl80211 {
+    pub fn connect() -> io::Result<Nl80211> {
+        let mut socket = Socket::open(netlink::NETLINK_GENERIC)?;
+        let family = netlink::resolve_family(&mut socket, "nl80211")?;
+
+        Ok(Nl80211{socket: socket, family: family})
+    }
+
+    pub fn interfaces(&mut self) -> io::Result<Vec<Interface>> {
+        let req = MsgBuilder::new(self.family.id, netlink::NLM_F_REQUEST | netlink::NLM_F_DUMP)
+            .genl(NL80211_CMD_GET_INTERFACE, 0);
+
+        Ok(parse_interfaces(&self.socket.request(req)?))
+    }
+
+    pub fn link(&mut self, device: &str) -> io::Result<Link> {
+        let index = self.interfaces()?.into_iter()
+            .find(|iface| iface.name == device)
+            .map(|iface| iface.index)
+            .ok_or(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("nl80211: no wireless interface '{}'", device)))?;
+
+        // SSID and frequency are properties of the interface.
+        let req = MsgBuilder::new(self.family.id, netlink::NLM_F_REQUEST)
+            .genl(NL80211_CMD_GET_INTERFACE, 0)
+            .attr_u32(NL80211_ATTR_IFINDEX, index);
+        let interface = self.socket.request(req)?;
+
+        // Signal and bitrate belong to the station (access point) we are
+        // associated with.
+        let req = MsgBuilder::new(self.family.id, netlink::NLM_F_REQUEST | netlink::NLM_F_DUMP)
+            .genl(NL80211_CMD_GET_STATION, 0)
+            .attr_u32(NL80211_ATTR_IFINDEX, index);
+        let station = self.socket.request(req)?;
+
+        Ok(parse_link(&interface, &station))
+    }
+
+    // A socket receiving connect and disconnect notifications.
+    pub fn events(&self) -> io::Result<Events> {
+        let group = self.family.groups.get(MLME_GROUP).map(|&g| g)
+            .ok_or(io::Error::new(
+                io::ErrorKind::NotFound, "nl80211: no mlme multicast group"))?;
+
+        let socket = Socket::open(netlink::NETLINK_GENERIC)?;
+        socket.add_membership(group)?;
+
+        Ok(Events{socket: socket, family: self.family.id})
+    }
+}
+
+pub struct Events {
+    socket: Socket,
+    family: u16,
+}
+
+impl Events {
+    // Block until the link is connected or disconnected.
+    pub fn wait(&self) -> io::Result<u8> {
+        loop {
+            for msg in self.socket.recv()? {
+                if msg.kind != self.family {
+                    continue;
+                }
+                match msg.genl_cmd() {
+                    Some(c @ NL80211_CMD_CONNECT) | Some(c @ NL80211_CMD_DISCONNECT) => return Ok(c),
+                    _ => (),
+                }
+            }
+        }
+    }
+}
+
+// Wireless interfaces in a NL80211_CMD_GET_INTERFACE dump.
+pub fn parse_interfaces(msgs: &[Msg]) -> Vec<Interface> {
+    let mut ifaces = Vec::new();
+    for msg in msgs {
+        let mut index = None;
+        let mut name = None;
+        for (kind, data) in msg.attrs(netlink::GENL_HDRLEN) {
+            match kind {
+                NL80211_ATTR_IFINDEX => index = netlink::attr_u32(data),
+                NL80211_ATTR_IFNAME => name = Some(netlink::attr_string(data)),
+                _ => (),
+            }
+        }
+
+        if let (Some(index), Some(name)) = (index, name) {
+            ifaces.push(Interface{index: index, name: name});
+        }
+    }
+
+    ifaces
+}
+
+// The link from the replies to NL80211_CMD_GET_INTERFACE and
+// NL80211_CMD_GET_STATION for one interface.
+pub fn parse_link(interface: &[Msg], station: &[Msg]) -> Link {
+    let mut link = Link {
+        ssid: None,
+        signal: None,
+        bitrate: None,
+        frequency: None,
+    };
+
+    for msg in interface {
+        for (kind, data) in msg.attrs(netlink::GENL_HDRLEN) {
+            match kind {
+                NL80211_ATTR_SSID => link.ssid = Some(String::from_utf8_lossy(data).into_owned()),
+                NL80211_ATTR_WIPHY_FREQ => link.frequency = netlink::attr_u32(data),
+                _ => (),
+            }
+        }
+    }
+
+    for msg in station {
+        for (kind, data) in msg.attrs(netlink::GENL_HDRLEN) {
+            if kind == NL80211_ATTR_STA_INFO {
+                parse_sta_info(data, &mut link);
+            }
+        }
+    }
+
+    link
+}
+
+fn parse_sta_info(data: &[u8], link: &mut Link) {
+    for (kind, data) in Attrs::new(data) {
+        match kind {
+            NL80211_STA_INFO_SIGNAL => {
+                link.signal = data.get(0).map(|&s| s as i8 as f64);
+            }
+            NL80211_STA_INFO_TX_BITRATE => {
+                // rates are in units of 100kbit/s. Prefer the 32 bit value
+                // as the 16 bit one saturates on fast links.
+                let mut rate16 = None;
+                let mut rate32 = None;
+                for (rkind, rdata) in Attrs::new(data) {
+                    match rkind {
+                        NL80211_RATE_INFO_BITRATE => rate16 = netlink::attr_u16(rdata).map(|r| r as u32),
+                        NL80211_RATE_INFO_BITRATE32 => rate32 = netlink::attr_u32(rdata),
+                        _ => (),
+                    }
+                }
+                link.bitrate = rate32.or(rate16).map(|r| r as f64 / 10.);
+            }
+            _ => (),
+        }
+    }
+}
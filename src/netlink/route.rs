@@ -0,0 +1,154 @@
+// Interface addresses and link events over rtnetlink, shared by the network
+// sensors.
+// see http://man7.org/linux/man-pages/man7/rtnetlink.7.html
+
+use libc;
+use netlink::{self, Msg, MsgBuilder, Socket};
+use std::collections::HashMap;
+use std::ffi::CString;
+use std::io;
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+const RTM_NEWADDR: u16 = 20;
+const RTM_GETADDR: u16 = 22;
+
+const IFA_ADDRESS: u16 = 1;
+const IFA_LOCAL: u16 = 2;
+
+// struct ifaddrmsg
+const IFADDRMSG_LEN: usize = 8;
+
+// multicast groups
+const RTNLGRP_LINK: u32 = 1;
+const RTNLGRP_IPV4_IFADDR: u32 = 5;
+const RTNLGRP_IPV6_IFADDR: u32 = 9;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Addresses {
+    pub ipv4: Vec<Ipv4Addr>,
+    pub ipv6: Vec<Ipv6Addr>,
+}
+
+impl Addresses {
+    pub fn is_empty(&self) -> bool {
+        self.ipv4.is_empty() && self.ipv6.is_empty()
+    }
+
+    // The address most worth showing. Global addresses win over link-local
+    // and loopback ones, then IPv4 wins over IPv6.
+    pub fn primary(&self) -> Option<String> {
+        self.ipv4.iter().find(|a| is_global_v4(a)).map(|a| a.to_string())
+            .or(self.ipv6.iter().find(|a| is_global_v6(a)).map(|a| a.to_string()))
+            .or(self.ipv4.first().map(|a| a.to_string()))
+            .or(self.ipv6.first().map(|a| a.to_string()))
+    }
+}
+
+// global in the rtnetlink sense: private ranges count, 169.254/16 doesn't.
+fn is_global_v4(addr: &Ipv4Addr) -> bool {
+    !addr.is_loopback() && !addr.is_link_local() && !addr.is_unspecified()
+}
+
+fn is_global_v6(addr: &Ipv6Addr) -> bool {
+    let link_local = addr.segments()[0] & 0xffc0 == 0xfe80;
+    !addr.is_loopback() && !addr.is_unspecified() && !link_local
+}
+
+pub fn if_index(device: &str) -> io::Result<u32> {
+    let name = CString::new(device)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+
+    match unsafe { libc::if_nametoindex(name.as_ptr()) } {
+        0 => Err(io::Error::last_os_error()),
+        index => Ok(index as u32),
+    }
+}
+
+// IPv4 and IPv6 addresses assigned to `device`.
+pub fn addresses(device: &str) -> io::Result<Addresses> {
+    let index = if_index(device)?;
+    let mut addrs = all_addresses()?;
+    Ok(addrs.remove(&index).unwrap_or_default())
+}
+
+// Every address on the system keyed by interface index, from a single dump.
+pub fn all_addresses() -> io::Result<HashMap<u32, Addresses>> {
+    let mut socket = Socket::open(netlink::NETLINK_ROUTE)?;
+
+    // the kernel ignores the filter in the header of a dump so there is no
+    // asking for a single interface.
+    let req = MsgBuilder::new(RTM_GETADDR, netlink::NLM_F_REQUEST | netlink::NLM_F_DUMP)
+        .header(&[libc::AF_UNSPEC as u8, 0, 0, 0, 0, 0, 0, 0]);
+
+    Ok(parse_addresses(&socket.request(req)?))
+}
+
+// Addresses in a RTM_NEWADDR dump, keyed by interface index.
+pub fn parse_addresses(msgs: &[Msg]) -> HashMap<u32, Addresses> {
+    let mut addrs: HashMap<u32, Addresses> = HashMap::new();
+
+    for msg in msgs {
+        if msg.kind != RTM_NEWADDR || msg.payload.len() < IFADDRMSG_LEN {
+            continue;
+        }
+
+        let family = msg.payload[0] as i32;
+        let index = netlink::attr_u32(&msg.payload[4..8]).unwrap_or(0);
+
+        // IFA_ADDRESS is the peer on point to point links so prefer
+        // IFA_LOCAL when it is present.
+        let mut local = None;
+        let mut address = None;
+        for (kind, data) in msg.attrs(IFADDRMSG_LEN) {
+            match kind {
+                IFA_LOCAL => local = Some(data),
+                IFA_ADDRESS => address = Some(data),
+                _ => (),
+            }
+        }
+
+        let entry = addrs.entry(index).or_insert_with(Addresses::default);
+        match (family, local.or(address)) {
+            (libc::AF_INET, Some(data)) if data.len() == 4 => {
+                entry.ipv4.push(Ipv4Addr::new(data[0], data[1], data[2], data[3]));
+            }
+            (libc::AF_INET6, Some(data)) if data.len() == 16 => {
+                let mut segments = [0u16; 8];
+                for (i, s) in segments.iter_mut().enumerate() {
+                    *s = (data[2 * i] as u16) << 8 | data[2 * i + 1] as u16;
+                }
+                entry.ipv6.push(Ipv6Addr::new(
+                    segments[0], segments[1], segments[2], segments[3],
+                    segments[4], segments[5], segments[6], segments[7]));
+            }
+            _ => (),
+        }
+    }
+
+    addrs
+}
+
+// Notifications for links going up or down and addresses changing.
+pub struct LinkEvents {
+    socket: Socket,
+}
+
+impl LinkEvents {
+    pub fn subscribe() -> io::Result<LinkEvents> {
+        let socket = Socket::open(netlink::NETLINK_ROUTE)?;
+        for &group in &[RTNLGRP_LINK, RTNLGRP_IPV4_IFADDR, RTNLGRP_IPV6_IFADDR] {
+            socket.add_membership(group)?;
+        }
+
+        Ok(LinkEvents{socket: socket})
+    }
+
+    // Block until any link or address changes.
+    pub fn wait(&self) -> io::Result<()> {
+        loop {
+            if !self.socket.recv()?.is_empty() {
+                return Ok(());
+            }
+        }
+    }
+}
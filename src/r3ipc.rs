@@ -18,6 +18,7 @@ pub const REPLY: u32 = 21;
 pub const UNPARK: u32 = 22;
 pub const WEBPACK: u32 = 23;
 pub const TICKER: u32 = 24;
+pub const SCREENSHOT: u32 = 25;
 
 pub trait R3Funcs {
     fn send_i3_message(&mut self, u32, &str) -> io::Result<()>;
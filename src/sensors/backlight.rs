@@ -0,0 +1,241 @@
+use error::BarError;
+use libc::{self, c_char, c_int};
+use message::Message;
+use sensors::{read_sys_file, Sensor, SensorResult};
+use std::ffi::CString;
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::str::from_utf8;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+static SYS_PATH: &'static str = "/sys";
+
+// Backlight types from most to least preferred. Firmware and platform
+// interfaces know about the panel, raw ones are just a register on the GPU.
+// see https://www.kernel.org/doc/Documentation/ABI/stable/sysfs-class-backlight
+static TYPE_PREFERENCE: &'static [&'static str] = &["firmware", "platform", "raw"];
+
+// see inotify(7). libc doesn't bind these yet.
+const IN_MODIFY: u32 = 0x00000002;
+const IN_CLOEXEC: c_int = libc::O_CLOEXEC;
+
+extern "C" {
+    fn inotify_init1(flags: c_int) -> c_int;
+    fn inotify_add_watch(fd: c_int, pathname: *const c_char, mask: u32) -> c_int;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BacklightInfo {
+    pub device: String,
+    pub brightness: u32,
+    pub max: u32,
+    pub percent: f64,
+}
+
+impl BacklightInfo {
+    pub fn new() -> BacklightInfo {
+        BacklightInfo{device: "".to_owned(), brightness: 0, max: 0, percent: 0.}
+    }
+}
+
+#[derive(Clone)]
+pub struct Backlight {
+    interval: Duration,
+    root: PathBuf,
+    device: Option<String>,
+    helper: Option<String>,
+}
+
+impl Backlight {
+    pub fn new(interval: Duration) -> Backlight {
+        Backlight {
+            interval: interval,
+            root: PathBuf::from(SYS_PATH),
+            device: None,
+            helper: None,
+        }
+    }
+
+    // A name under /sys/class/backlight, e.g. "intel_backlight". Without
+    // one a device is picked by its type.
+    pub fn with_device(mut self, device: &str) -> Backlight {
+        self.device = Some(device.to_owned());
+        self
+    }
+
+    // Called with the new raw brightness when the brightness file isn't
+    // writable, e.g. `brightnessctl set`.
+    pub fn with_helper(mut self, helper: &str) -> Backlight {
+        self.helper = Some(helper.to_owned());
+        self
+    }
+
+    pub fn with_root<P: AsRef<Path>>(mut self, root: P) -> Backlight {
+        self.root = root.as_ref().to_path_buf();
+        self
+    }
+
+    fn device_dir(&self) -> Result<PathBuf, BarError> {
+        let class = self.root.join("class/backlight");
+        if let Some(ref device) = self.device {
+            return Ok(class.join(device));
+        }
+
+        // devices without a known type go last, ties by name.
+        let mut dirs: Vec<(usize, PathBuf)> = fs::read_dir(&class)?
+            .filter_map(|e| e.ok())
+            .map(|e| {
+                let path = e.path();
+                let rank = read_sys_file(path.join("type"))
+                    .and_then(|t| TYPE_PREFERENCE.iter().position(|&p| p == t))
+                    .unwrap_or(TYPE_PREFERENCE.len());
+                (rank, path)
+            })
+            .collect();
+        dirs.sort();
+
+        dirs.into_iter().next()
+            .map(|(_, path)| path)
+            .ok_or(BarError::Bar("backlight: no devices".to_owned()))
+    }
+
+    pub fn read(&self) -> Result<BacklightInfo, BarError> {
+        let dir = self.device_dir()?;
+        let device = dir.file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or("".to_owned());
+
+        let brightness = read_u32(&dir.join("brightness"))?;
+        let max = read_u32(&dir.join("max_brightness"))?;
+        let percent = if max > 0 { brightness as f64 / max as f64 * 100. } else { 0. };
+
+        Ok(BacklightInfo {
+            device: device,
+            brightness: brightness,
+            max: max,
+            percent: percent,
+        })
+    }
+
+    // Move brightness by `delta` percentage points.
+    pub fn adjust(&self, delta: f64) -> Result<(), BarError> {
+        let info = self.read()?;
+        let value = brightness_for(info.percent + delta, info.max);
+        if value == info.brightness {
+            return Ok(());
+        }
+
+        let path = self.device_dir()?.join("brightness");
+        match File::create(&path).and_then(|mut f| write!(f, "{}", value)) {
+            Ok(_) => Ok(()),
+            Err(ref e) if e.kind() == io::ErrorKind::PermissionDenied && self.helper.is_some() => {
+                let helper = self.helper.as_ref().unwrap();
+                let output = Command::new(helper).arg(value.to_string()).output()?;
+
+                if !output.status.success() {
+                    return Err(
+                        BarError::Bar(
+                            format!("'{} {}' ERROR: {}",
+                                    helper, value, from_utf8(&output.stderr)?)));
+                }
+                Ok(())
+            }
+            Err(e) => Err(BarError::Io(e)),
+        }
+    }
+}
+
+impl Sensor for Backlight {
+    fn run(&self, tx: mpsc::Sender<Message>) -> SensorResult {
+
+        let iv = self.interval;
+        let sensor = self.clone();
+        let dir = self.device_dir()?;
+
+        tx.send(Message::Backlight(sensor.read()?)).unwrap();
+
+        let handle = thread::spawn(move || {
+            loop {
+                thread::park_timeout(iv);
+
+                if let Err(e) = sensor.read().map_err(|e| e.to_string())
+                    .and_then(|info| tx.send(Message::Backlight(info))
+                              .map_err(|e| e.to_string())) {
+                        println!("backlight sensor ERROR: {}", e);
+                    }
+            }
+        });
+
+        let sensor = handle.thread().clone();
+        thread::spawn(move || {
+            if let Err(e) = watch_brightness(&dir, || sensor.unpark()) {
+                println!("backlight watch ERROR: {}", e);
+            }
+        });
+
+        Ok(handle)
+    }
+}
+
+// Raw brightness for a percentage of `max`. Never zero, some panels switch
+// off completely and there'd be nothing to scroll back up on.
+pub fn brightness_for(percent: f64, max: u32) -> u32 {
+    let value = (percent.max(0.).min(100.) / 100. * max as f64).round() as u32;
+    value.max(1).min(max)
+}
+
+fn read_u32(path: &Path) -> Result<u32, BarError> {
+    let mut contents = String::new();
+    File::open(path)?.read_to_string(&mut contents)?;
+    contents.trim().parse::<u32>()
+        .map_err(|e| BarError::Bar(format!("{}: {}", path.display(), e)))
+}
+
+// Writes to `brightness` modify it directly, while hotkeys handled by the
+// firmware are announced through `actual_brightness`.
+fn watch_brightness<F: Fn()>(dir: &Path, on_change: F) -> Result<(), BarError> {
+    let fd = unsafe { inotify_init1(IN_CLOEXEC) };
+    if fd < 0 {
+        return Err(BarError::Io(io::Error::last_os_error()));
+    }
+
+    for name in &["brightness", "actual_brightness"] {
+        // not every driver has actual_brightness.
+        let path = dir.join(name);
+        if !path.exists() {
+            continue;
+        }
+
+        let path = CString::new(path.to_string_lossy().into_owned())
+            .map_err(|e| BarError::Bar(e.to_string()));
+        let watch = path.and_then(|p| match unsafe { inotify_add_watch(fd, p.as_ptr(), IN_MODIFY) } {
+            -1 => Err(BarError::Io(io::Error::last_os_error())),
+            _ => Ok(()),
+        });
+
+        if let Err(e) = watch {
+            unsafe { libc::close(fd) };
+            return Err(e);
+        }
+    }
+
+    // the events themselves don't matter, only that something changed.
+    let mut buf = [0u8; 4096];
+    loop {
+        let n = unsafe { libc::read(fd, buf.as_mut_ptr() as *mut libc::c_void, buf.len()) };
+        if n < 0 {
+            let err = io::Error::last_os_error();
+            if err.kind() == io::ErrorKind::Interrupted {
+                continue;
+            }
+            unsafe { libc::close(fd) };
+            return Err(BarError::Io(err));
+        }
+
+        on_change();
+    }
+}
@@ -0,0 +1,275 @@
+use error::BarError;
+use i3bar::ClickEvent;
+use message::Message;
+use sensors::{Sensor, SensorResult};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::process::{ChildStdin, Command, Stdio};
+use std::str::from_utf8;
+use std::sync::{Arc, Mutex, mpsc};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// Exit status a block uses to ask for attention, as in i3blocks.
+const URGENT_EXIT: i32 = 33;
+
+// One update from a block command. Following i3blocks the first line of
+// output is the full text, the second the short text and the third a
+// colour such as "#FF0000".
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommandOutput {
+    pub full_text: String,
+    pub short_text: Option<String>,
+    pub color: Option<String>,
+    pub urgent: bool,
+}
+
+impl CommandOutput {
+    pub fn new() -> CommandOutput {
+        CommandOutput {
+            full_text: "".to_owned(),
+            short_text: None,
+            color: None,
+            urgent: false,
+        }
+    }
+}
+
+pub struct CommandSensor {
+    name: String,
+    command: String,
+    interval: Option<Duration>,
+    timeout: Duration,
+}
+
+impl CommandSensor {
+    // `command` is run with `sh -c`. Without an interval it only runs at
+    // start up and whenever the sensor is unparked.
+    pub fn new(name: &str, command: &str, interval: Option<Duration>) -> CommandSensor {
+        CommandSensor {
+            name: name.to_owned(),
+            command: command.to_owned(),
+            interval: interval,
+            timeout: Duration::from_secs(5),
+        }
+    }
+
+    // Commands still running after `timeout` are killed.
+    pub fn with_timeout(mut self, timeout: Duration) -> CommandSensor {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl Sensor for CommandSensor {
+    fn run(&self, tx: mpsc::Sender<Message>) -> SensorResult {
+
+        let iv = self.interval;
+        let name = self.name.clone();
+        let command = self.command.clone();
+        let timeout = self.timeout;
+
+        Ok(thread::spawn(move || {
+            loop {
+                if let Err(e) = run_command(&command, timeout).map_err(|e| e.to_string())
+                    .and_then(|out| tx.send(Message::Command(name.clone(), out))
+                              .map_err(|e| e.to_string())) {
+                        println!("command sensor '{}' ERROR: {}", name, e);
+                    }
+
+                match iv {
+                    Some(iv) => thread::park_timeout(iv),
+                    None => thread::park(),
+                }
+            }
+        }))
+    }
+}
+
+// A block script that keeps running and prints a line whenever its value
+// changes, i3blocks' `interval=persist`. Clicks are written to its stdin
+// one JSON object per line.
+pub struct PersistentCommand {
+    name: String,
+    command: String,
+    stdin: Arc<Mutex<Option<ChildStdin>>>,
+}
+
+impl PersistentCommand {
+    pub fn new(name: &str, command: &str) -> PersistentCommand {
+        PersistentCommand {
+            name: name.to_owned(),
+            command: command.to_owned(),
+            stdin: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    // A handle for sending clicks to whichever child is currently running.
+    pub fn clicker(&self) -> Clicker {
+        Clicker{name: self.name.clone(), stdin: self.stdin.clone()}
+    }
+}
+
+#[derive(Clone)]
+pub struct Clicker {
+    name: String,
+    stdin: Arc<Mutex<Option<ChildStdin>>>,
+}
+
+impl Clicker {
+    // Clicks while the script is restarting are dropped.
+    pub fn click(&self, event: &ClickEvent) -> Result<(), BarError> {
+        let mut stdin = self.stdin.lock().unwrap();
+        if let Some(ref mut pipe) = *stdin {
+            let mut event = event.clone();
+            event.name = self.name.clone();
+            writeln!(pipe, "{}", event.to_json())?;
+            pipe.flush()?;
+        }
+        Ok(())
+    }
+}
+
+impl Sensor for PersistentCommand {
+    fn run(&self, tx: mpsc::Sender<Message>) -> SensorResult {
+
+        let name = self.name.clone();
+        let command = self.command.clone();
+        let stdin = self.stdin.clone();
+
+        Ok(thread::spawn(move || {
+            respawn(&format!("command sensor '{}'", name), &command, &stdin, |_, line| {
+                tx.send(Message::Command(name.clone(), parse_output(line, false)))
+                    .map_err(|e| BarError::Bar(e.to_string()))
+            })
+        }))
+    }
+}
+
+// Keeps `command` running, calling `on_line` with the number and text of
+// every line it prints. While it runs its stdin is in `stdin`, made into
+// whatever the caller writes to, and whenever it exits it's restarted.
+pub fn respawn<T, F>(name: &str, command: &str, stdin: &Arc<Mutex<Option<T>>>, mut on_line: F) -> !
+    where T: From<ChildStdin>,
+          F: FnMut(usize, &str) -> Result<(), BarError>
+{
+    let mut backoff = Duration::from_secs(1);
+
+    loop {
+        let start = Instant::now();
+        if let Err(e) = supervise(command, stdin, &mut on_line) {
+            println!("{} ERROR: {}", name, e);
+        }
+        *stdin.lock().unwrap() = None;
+
+        // a command that keeps dying straight away shouldn't spin.
+        if start.elapsed() > Duration::from_secs(30) {
+            backoff = Duration::from_secs(1);
+        } else {
+            backoff = ::std::cmp::min(backoff * 2, Duration::from_secs(60));
+        }
+        thread::sleep(backoff);
+    }
+}
+
+// Runs the command until it exits or `on_line` fails.
+fn supervise<T, F>(command: &str, stdin: &Arc<Mutex<Option<T>>>, on_line: &mut F)
+                   -> Result<(), BarError>
+    where T: From<ChildStdin>,
+          F: FnMut(usize, &str) -> Result<(), BarError>
+{
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()?;
+
+    *stdin.lock().unwrap() = child.stdin.take().map(T::from);
+
+    let stdout = BufReader::new(child.stdout.take().unwrap());
+    for (n, line) in stdout.lines().enumerate() {
+        if let Err(e) = line.map_err(BarError::from).and_then(|line| on_line(n, &line)) {
+            // don't leave it running alongside the next one.
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(e);
+        }
+    }
+
+    let status = child.wait()?;
+    Err(BarError::Bar(format!("'{}' exited: {}", command, status)))
+}
+
+pub fn run_command(command: &str, timeout: Duration) -> Result<CommandOutput, BarError> {
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    // drain the pipes as we go so a chatty command can't block on a full
+    // pipe and be mistaken for a slow one.
+    let mut stdout = child.stdout.take().unwrap();
+    let mut stderr = child.stderr.take().unwrap();
+    let out_reader = thread::spawn(move || {
+        let mut buf = Vec::new();
+        stdout.read_to_end(&mut buf).map(|_| buf)
+    });
+    let err_reader = thread::spawn(move || {
+        let mut buf = Vec::new();
+        stderr.read_to_end(&mut buf).map(|_| buf)
+    });
+
+    let start = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+
+        if start.elapsed() >= timeout {
+            child.kill()?;
+            child.wait()?;
+            return Err(BarError::Bar(format!("'{}' timed out", command)));
+        }
+
+        thread::sleep(Duration::from_millis(10));
+    };
+
+    let stdout = out_reader.join().unwrap()?;
+    let stderr = err_reader.join().unwrap()?;
+
+    match status.code() {
+        Some(0) | Some(URGENT_EXIT) => {
+            Ok(parse_output(from_utf8(&stdout)?, status.code() == Some(URGENT_EXIT)))
+        }
+        _ => Err(BarError::Bar(format!("'{}' ERROR: {}", command, from_utf8(&stderr)?.trim()))),
+    }
+}
+
+pub fn parse_output(stdout: &str, urgent: bool) -> CommandOutput {
+    let mut lines = stdout.lines();
+    let mut line = || lines.next()
+        .map(|l| l.trim())
+        .and_then(|l| if l.is_empty() { None } else { Some(l.to_owned()) });
+
+    let full_text = line().unwrap_or("".to_owned());
+    let short_text = line();
+    let color = line();
+
+    CommandOutput {
+        full_text: full_text,
+        short_text: short_text,
+        color: color,
+        urgent: urgent,
+    }
+}
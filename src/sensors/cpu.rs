@@ -0,0 +1,189 @@
+use error::BarError;
+use message::Message;
+use sensors::{Sensor, SensorResult};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+static STAT_PATH: &'static str = "/proc/stat";
+
+// Jiffies spent in each state since boot.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CpuTimes {
+    pub user: u64,
+    pub nice: u64,
+    pub system: u64,
+    pub idle: u64,
+    pub iowait: u64,
+    pub irq: u64,
+    pub softirq: u64,
+    pub steal: u64,
+}
+
+impl CpuTimes {
+    fn total(&self) -> u64 {
+        self.user + self.nice + self.system + self.idle +
+            self.iowait + self.irq + self.softirq + self.steal
+    }
+}
+
+// Cores are keyed by the N in their `cpuN` label as offline cores are left
+// out of /proc/stat.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stat {
+    pub total: CpuTimes,
+    pub cores: BTreeMap<u32, CpuTimes>,
+}
+
+// Percentages of the time between two samples. `busy` excludes iowait and
+// steal which are reported on their own.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CoreUsage {
+    pub busy: f64,
+    pub iowait: f64,
+    pub steal: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CpuUsage {
+    pub total: CoreUsage,
+    pub cores: Vec<CoreUsage>,
+}
+
+impl CpuUsage {
+    pub fn new() -> CpuUsage {
+        CpuUsage {
+            total: CoreUsage{busy: 0., iowait: 0., steal: 0.},
+            cores: Vec::new(),
+        }
+    }
+}
+
+pub struct Cpu {
+    interval: Duration,
+    path: PathBuf,
+}
+
+impl Cpu {
+    pub fn new(interval: Duration) -> Cpu {
+        Cpu{interval: interval, path: PathBuf::from(STAT_PATH)}
+    }
+
+    pub fn with_path<P: AsRef<Path>>(mut self, path: P) -> Cpu {
+        self.path = path.as_ref().to_path_buf();
+        self
+    }
+}
+
+impl Sensor for Cpu {
+    fn run(&self, tx: mpsc::Sender<Message>) -> SensorResult {
+
+        let iv = self.interval;
+        let path = self.path.clone();
+        let mut last = read_stat(&path)?;
+
+        Ok(thread::spawn(move || {
+            loop {
+                thread::park_timeout(iv);
+
+                match read_stat(&path) {
+                    Ok(next) => {
+                        let cpu = usage(&last, &next);
+                        last = next;
+
+                        if let Err(e) = tx.send(Message::Cpu(cpu)) {
+                            println!("cpu sensor ERROR: {}", e);
+                        }
+                    }
+                    Err(e) => println!("cpu sensor ERROR: {}", e),
+                }
+            }
+        }))
+    }
+}
+
+pub fn read_stat<P: AsRef<Path>>(path: P) -> Result<Stat, BarError> {
+    let mut contents = String::new();
+    File::open(path)?.read_to_string(&mut contents)?;
+    parse_stat(&contents)
+}
+
+// cpu  4705 356 584 3699176 23 23 0 0 0 0
+// cpu0 1393280 32966 572056 13343292 6130 0 17875 0 23933 0
+pub fn parse_stat(contents: &str) -> Result<Stat, BarError> {
+    let mut total = None;
+    let mut cores = BTreeMap::new();
+
+    for line in contents.lines() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let label = match fields.first() {
+            Some(label) if label.starts_with("cpu") => *label,
+            _ => continue,
+        };
+
+        // older kernels don't report every column.
+        let mut values = [0u64; 8];
+        for (v, f) in values.iter_mut().zip(fields[1..].iter()) {
+            *v = f.parse::<u64>()
+                .map_err(|e| BarError::Bar(format!("/proc/stat '{}': {}", label, e)))?;
+        }
+
+        let times = CpuTimes {
+            user: values[0],
+            nice: values[1],
+            system: values[2],
+            idle: values[3],
+            iowait: values[4],
+            irq: values[5],
+            softirq: values[6],
+            steal: values[7],
+        };
+
+        if label == "cpu" {
+            total = Some(times);
+        } else {
+            let core = label[3..].parse::<u32>()
+                .map_err(|e| BarError::Bar(format!("/proc/stat '{}': {}", label, e)))?;
+            cores.insert(core, times);
+        }
+    }
+
+    let total = total.ok_or(BarError::Bar("/proc/stat: no cpu line".to_owned()))?;
+    Ok(Stat{total: total, cores: cores})
+}
+
+// Usage of the cores present in both samples, in core order. A core that
+// was hotplugged or taken offline in between is left out.
+pub fn usage(prev: &Stat, next: &Stat) -> CpuUsage {
+    CpuUsage {
+        total: core_usage(&prev.total, &next.total),
+        cores: next.cores.iter()
+            .filter_map(|(core, n)| prev.cores.get(core).map(|p| core_usage(p, n)))
+            .collect(),
+    }
+}
+
+fn core_usage(prev: &CpuTimes, next: &CpuTimes) -> CoreUsage {
+    // counters go backwards when a core is hotplugged.
+    let d = |p: u64, n: u64| n.saturating_sub(p) as f64;
+
+    let total = d(prev.total(), next.total());
+    if total == 0. {
+        return CoreUsage{busy: 0., iowait: 0., steal: 0.};
+    }
+
+    let idle = d(prev.idle, next.idle);
+    let iowait = d(prev.iowait, next.iowait);
+    let steal = d(prev.steal, next.steal);
+    let busy = (total - idle - iowait - steal).max(0.);
+
+    CoreUsage {
+        busy: busy / total * 100.,
+        iowait: iowait / total * 100.,
+        steal: steal / total * 100.,
+    }
+}
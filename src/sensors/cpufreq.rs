@@ -0,0 +1,152 @@
+use error::BarError;
+use message::Message;
+use sensors::{read_sys_file, Sensor, SensorResult};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::str::from_utf8;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+static SYS_PATH: &'static str = "/sys";
+
+// The order clicking the widget steps through profiles. Profiles the
+// firmware doesn't offer are skipped.
+static PROFILE_CYCLE: &'static [&'static str] = &["low-power", "balanced", "performance"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CpuFreqInfo {
+    // MHz, one per core.
+    pub frequencies: Vec<u32>,
+    pub governor: Option<String>,
+    pub profile: Option<String>,
+    pub profiles: Vec<String>,
+}
+
+impl CpuFreqInfo {
+    pub fn new() -> CpuFreqInfo {
+        CpuFreqInfo {
+            frequencies: Vec::new(),
+            governor: None,
+            profile: None,
+            profiles: Vec::new(),
+        }
+    }
+
+    // The profile after the current one, wrapping around.
+    pub fn next_profile(&self) -> Option<String> {
+        let cycle: Vec<&str> = PROFILE_CYCLE.iter()
+            .map(|&p| p)
+            .filter(|p| self.profiles.iter().any(|c| c == p))
+            .collect();
+
+        if cycle.is_empty() {
+            return None;
+        }
+
+        let next = match self.profile.as_ref().and_then(|cur| cycle.iter().position(|p| p == cur)) {
+            Some(i) => (i + 1) % cycle.len(),
+            None => 0,
+        };
+
+        Some(cycle[next].to_owned())
+    }
+}
+
+#[derive(Clone)]
+pub struct CpuFreq {
+    interval: Duration,
+    root: PathBuf,
+}
+
+impl CpuFreq {
+    pub fn new(interval: Duration) -> CpuFreq {
+        CpuFreq{interval: interval, root: PathBuf::from(SYS_PATH)}
+    }
+
+    pub fn with_root<P: AsRef<Path>>(mut self, root: P) -> CpuFreq {
+        self.root = root.as_ref().to_path_buf();
+        self
+    }
+
+    pub fn read(&self) -> Result<CpuFreqInfo, BarError> {
+        let mut cores: Vec<(u32, PathBuf)> = fs::read_dir(self.root.join("devices/system/cpu"))?
+            .filter_map(|e| e.ok())
+            .filter_map(|e| {
+                let name = e.file_name().to_string_lossy().into_owned();
+                if name.starts_with("cpu") {
+                    name[3..].parse::<u32>().ok().map(|n| (n, e.path().join("cpufreq")))
+                } else {
+                    None
+                }
+            })
+            .collect();
+        cores.sort();
+
+        let mut frequencies = Vec::new();
+        let mut governor = None;
+        for (_, cpufreq) in cores {
+            if let Some(khz) = read_sys_file(cpufreq.join("scaling_cur_freq"))
+                .and_then(|s| s.parse::<u32>().ok()) {
+                    frequencies.push(khz / 1000);
+                }
+
+            // governors are almost always set for every core at once.
+            if governor.is_none() {
+                governor = read_sys_file(cpufreq.join("scaling_governor"));
+            }
+        }
+
+        let acpi = self.root.join("firmware/acpi");
+        let profiles = read_sys_file(acpi.join("platform_profile_choices"))
+            .map(|s| s.split_whitespace().map(|p| p.to_owned()).collect())
+            .unwrap_or(Vec::new());
+
+        Ok(CpuFreqInfo {
+            frequencies: frequencies,
+            governor: governor,
+            profile: read_sys_file(acpi.join("platform_profile")),
+            profiles: profiles,
+        })
+    }
+
+    // Writing platform_profile needs root so hand the new profile to a
+    // helper, e.g. a script run through sudo or powerprofilesctl.
+    pub fn set_profile(helper: &str, profile: &str) -> Result<(), BarError> {
+        let output = Command::new(helper)
+            .arg(profile)
+            .output()?;
+
+        if !output.status.success() {
+            return Err(
+                BarError::Bar(
+                    format!("'{} {}' ERROR: {}",
+                            helper, profile, from_utf8(&output.stderr)?)));
+        }
+
+        Ok(())
+    }
+}
+
+impl Sensor for CpuFreq {
+    fn run(&self, tx: mpsc::Sender<Message>) -> SensorResult {
+
+        let iv = self.interval;
+        let sensor = self.clone();
+
+        tx.send(Message::CpuFreq(sensor.read()?)).unwrap();
+
+        Ok(thread::spawn(move || {
+            loop {
+                thread::park_timeout(iv);
+
+                if let Err(e) = sensor.read().map_err(|e| e.to_string())
+                    .and_then(|info| tx.send(Message::CpuFreq(info))
+                              .map_err(|e| e.to_string())) {
+                        println!("cpufreq sensor ERROR: {}", e);
+                    }
+            }
+        }))
+    }
+}
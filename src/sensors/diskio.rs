@@ -0,0 +1,206 @@
+use error::BarError;
+use message::Message;
+use sensors::{Sensor, SensorResult};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+static DISKSTATS_PATH: &'static str = "/proc/diskstats";
+static BLOCK_PATH: &'static str = "/sys/block";
+
+// diskstats always counts in 512 byte sectors whatever the device's own
+// sector size.
+const SECTOR_SIZE: u64 = 512;
+
+// Prefixes of virtual devices skipped when no devices are chosen.
+static VIRTUAL_DEVICES: &'static [&'static str] = &["loop", "ram", "zram"];
+
+// Counters for one block device as read from /proc/diskstats.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Counters {
+    pub sectors_read: u64,
+    pub sectors_written: u64,
+    // milliseconds spent with I/O in flight.
+    pub io_ms: u64,
+}
+
+// Bytes per second, and the percentage of time the device was busy.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeviceIo {
+    pub name: String,
+    pub read: f64,
+    pub write: f64,
+    pub busy: f64,
+}
+
+pub struct DiskIo {
+    interval: Duration,
+    devices: Vec<String>,
+    path: PathBuf,
+    block_path: PathBuf,
+}
+
+impl DiskIo {
+    // An empty list of devices samples every whole disk, skipping
+    // partitions, loop, ram and zram devices and devices stacked on others
+    // such as dm-crypt and md RAID, whose I/O the disks below them count.
+    pub fn new(interval: Duration, devices: Vec<String>) -> DiskIo {
+        DiskIo {
+            interval: interval,
+            devices: devices,
+            path: PathBuf::from(DISKSTATS_PATH),
+            block_path: PathBuf::from(BLOCK_PATH),
+        }
+    }
+
+    pub fn with_paths<P, Q>(mut self, diskstats: P, block: Q) -> DiskIo
+        where P: AsRef<Path>, Q: AsRef<Path>
+    {
+        self.path = diskstats.as_ref().to_path_buf();
+        self.block_path = block.as_ref().to_path_buf();
+        self
+    }
+}
+
+impl Sensor for DiskIo {
+    fn run(&self, tx: mpsc::Sender<Message>) -> SensorResult {
+
+        let iv = self.interval;
+        let path = self.path.clone();
+        let block_path = self.block_path.clone();
+        let devices = self.devices.clone();
+
+        let mut last = read_diskstats(&path, &block_path, &devices)?;
+        let mut last_time = Instant::now();
+
+        Ok(thread::spawn(move || {
+            loop {
+                thread::park_timeout(iv);
+
+                match read_diskstats(&path, &block_path, &devices) {
+                    Ok(next) => {
+                        let now = Instant::now();
+                        let io = rates(&last, &next, now.duration_since(last_time));
+                        last = next;
+                        last_time = now;
+
+                        if let Err(e) = tx.send(Message::DiskIo(io)) {
+                            println!("diskio sensor ERROR: {}", e);
+                        }
+                    }
+                    Err(e) => println!("diskio sensor ERROR: {}", e),
+                }
+            }
+        }))
+    }
+}
+
+pub fn read_diskstats<P, Q>(path: P, block_path: Q, devices: &[String])
+                            -> Result<HashMap<String, Counters>, BarError>
+    where P: AsRef<Path>, Q: AsRef<Path>
+{
+    let mut contents = String::new();
+    File::open(path)?.read_to_string(&mut contents)?;
+
+    let mut counters = parse_diskstats(&contents)?;
+    if devices.is_empty() {
+        let names: Vec<String> = counters.keys().cloned().collect();
+        counters.retain(|name, _| {
+            !VIRTUAL_DEVICES.iter().any(|v| name.starts_with(v)) &&
+                !is_partition(name, &names) &&
+                !is_stacked(name, block_path.as_ref())
+        });
+    } else {
+        counters.retain(|name, _| devices.contains(name));
+    }
+
+    Ok(counters)
+}
+
+//    8       0 sda 4613 1266 288710 2185 7413 5623 267234 9614 0 7020 12233 ...
+//    8       1 sda1 4567 1266 286278 2165 7413 5623 267234 9614 0 7000 11779 ...
+// see https://www.kernel.org/doc/Documentation/iostats.txt
+pub fn parse_diskstats(contents: &str) -> Result<HashMap<String, Counters>, BarError> {
+    let mut counters = HashMap::new();
+
+    for line in contents.lines() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.is_empty() {
+            continue;
+        }
+        if fields.len() < 14 {
+            return Err(BarError::Bar(format!("/proc/diskstats: short line '{}'", line.trim())));
+        }
+
+        let name = fields[2];
+        let parse = |f: &str| f.parse::<u64>()
+            .map_err(|e| BarError::Bar(format!("/proc/diskstats '{}': {}", name, e)));
+
+        counters.insert(name.to_owned(), Counters {
+            sectors_read: parse(fields[5])?,
+            sectors_written: parse(fields[9])?,
+            io_ms: parse(fields[12])?,
+        });
+    }
+
+    Ok(counters)
+}
+
+// Rates for every device present in `next`. Devices that are new since
+// `prev` report zero until the following sample.
+pub fn rates(prev: &HashMap<String, Counters>,
+             next: &HashMap<String, Counters>,
+             elapsed: Duration) -> Vec<DeviceIo> {
+
+    let secs = elapsed.as_secs() as f64 + elapsed.subsec_nanos() as f64 * 1e-9;
+
+    let mut io: Vec<DeviceIo> = next.iter().map(|(name, n)| {
+        // counters only go backwards when a device is removed and re-added.
+        let (read, write, busy) = match prev.get(name) {
+            Some(p) if secs > 0. => (
+                (n.sectors_read.saturating_sub(p.sectors_read) * SECTOR_SIZE) as f64 / secs,
+                (n.sectors_written.saturating_sub(p.sectors_written) * SECTOR_SIZE) as f64 / secs,
+                (n.io_ms.saturating_sub(p.io_ms) as f64 / (secs * 10.)).min(100.),
+            ),
+            _ => (0., 0., 0.),
+        };
+
+        DeviceIo{name: name.clone(), read: read, write: write, busy: busy}
+    }).collect();
+
+    io.sort_by(|a, b| a.name.cmp(&b.name));
+    io
+}
+
+// sda1 belongs to sda and nvme0n1p1 to nvme0n1. Disks whose names end in a
+// digit separate the partition number with a 'p', so dm-10 isn't dm-1's.
+fn is_partition(name: &str, names: &[String]) -> bool {
+    names.iter().any(|disk| {
+        if name.len() <= disk.len() || !name.starts_with(disk.as_str()) {
+            return false;
+        }
+
+        let number = &name[disk.len()..];
+        let number = if disk.ends_with(|c: char| c.is_digit(10)) {
+            if !number.starts_with('p') {
+                return false;
+            }
+            &number[1..]
+        } else {
+            number
+        };
+
+        !number.is_empty() && number.chars().all(|c| c.is_digit(10))
+    })
+}
+
+// dm-* and md* devices list the devices they sit on in slaves/.
+fn is_stacked(name: &str, block_path: &Path) -> bool {
+    fs::read_dir(block_path.join(name).join("slaves"))
+        .map(|mut slaves| slaves.next().is_some())
+        .unwrap_or(false)
+}
@@ -1,21 +1,115 @@
 use error::BarError;
-use sensors;
+use libc;
+use sensors::{self, Level};
 use message::Message;
-use std::process::Command;
-use std::str::from_utf8;
+use std::collections::HashMap;
+use std::ffi::CString;
+use std::fs::File;
+use std::io::{Read, self};
+use std::mem;
+use std::os::unix::io::AsRawFd;
 use std::sync::mpsc;
 use std::thread;
 use std::time::Duration;
 
+static MOUNTINFO_PATH: &'static str = "/proc/self/mountinfo";
+
+// Filesystems that don't store anything on a disk we care about.
+static PSEUDO_FS: &'static [&'static str] = &[
+    "autofs", "binfmt_misc", "bpf", "cgroup", "cgroup2", "configfs",
+    "debugfs", "devpts", "devtmpfs", "efivarfs", "fusectl", "hugetlbfs",
+    "mqueue", "nsfs", "overlay", "proc", "pstore", "ramfs", "rpc_pipefs",
+    "securityfs", "selinuxfs", "squashfs", "sysfs", "tmpfs", "tracefs",
+];
+
+// FUSE daemons exposing virtual trees. statvfs on these can block on the
+// daemon, so they are dropped by their full fstype.
+static VIRTUAL_FUSE: &'static [&'static str] = &[
+    "fuse.gvfsd-fuse", "fuse.portal",
+];
+
+#[derive(Debug, Clone)]
+enum Mounts {
+    Fixed(Vec<String>),
+    Discover,
+}
+
 #[derive(Debug)]
 pub struct DiskUsage {
     interval: Duration,
-    mountpoints: Vec<String>,
+    mounts: Mounts,
+    thresholds: HashMap<String, Threshold>,
+    default_threshold: Option<Threshold>,
+}
+
+// Percent used at which a mount is considered nearly full.
+#[derive(Debug, Clone, Copy)]
+pub struct Threshold {
+    pub warning: f64,
+    pub critical: f64,
+}
+
+impl Threshold {
+    pub fn new(warning: f64, critical: f64) -> Threshold {
+        Threshold{warning: warning, critical: critical}
+    }
+
+    fn level(&self, percent: f64) -> Level {
+        Level::of(percent, self.warning, self.critical)
+    }
+}
+
+// Sizes are in bytes.
+#[derive(Clone, Debug)]
+pub struct MountUsage {
+    pub mountpoint: String,
+    pub total: u64,
+    pub used: u64,
+    pub available: u64,
+    pub percent: f64,
+    pub inodes_total: u64,
+    pub inodes_used: u64,
+    pub inodes_percent: f64,
+    pub level: Level,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Mount {
+    pub device: String,
+    pub mountpoint: String,
+    pub fstype: String,
+    pub source: String,
 }
 
 impl DiskUsage {
     pub fn new(interval: Duration, mountpoints: Vec<String>) -> Self {
-        DiskUsage{interval: interval, mountpoints: mountpoints}
+        DiskUsage{
+            interval: interval,
+            mounts: Mounts::Fixed(mountpoints),
+            thresholds: HashMap::new(),
+            default_threshold: None,
+        }
+    }
+
+    // Report every real filesystem in /proc/self/mountinfo. Mounts are
+    // re-read on each update so removable media come and go.
+    pub fn discover(interval: Duration) -> Self {
+        DiskUsage{
+            interval: interval,
+            mounts: Mounts::Discover,
+            thresholds: HashMap::new(),
+            default_threshold: None,
+        }
+    }
+
+    pub fn with_threshold(mut self, mountpoint: &str, threshold: Threshold) -> Self {
+        self.thresholds.insert(mountpoint.to_owned(), threshold);
+        self
+    }
+
+    pub fn with_default_threshold(mut self, threshold: Threshold) -> Self {
+        self.default_threshold = Some(threshold);
+        self
     }
 }
 
@@ -23,60 +117,202 @@ impl sensors::Sensor for DiskUsage {
     fn run(&self, tx: mpsc::Sender<Message>) -> sensors::SensorResult {
 
         let iv = self.interval;
-        let mountpoints = self.mountpoints.clone();
+        let mounts = self.mounts.clone();
+        let thresholds = self.thresholds.clone();
+        let default_threshold = self.default_threshold;
+
+        let update = move || -> Result<Vec<MountUsage>, BarError> {
+            let mountpoints = match mounts {
+                Mounts::Fixed(ref mps) => mps.clone(),
+                Mounts::Discover => read_mountinfo()?.into_iter()
+                    .map(|m| m.mountpoint)
+                    .collect(),
+            };
 
-        tx.send(Message::DiskUsage(get_usage(mountpoints.clone())?)).unwrap();
+            let mut usages = get_usage(&mountpoints);
+            for usage in usages.iter_mut() {
+                let threshold = thresholds.get(&usage.mountpoint)
+                    .or(default_threshold.as_ref());
+                if let Some(t) = threshold {
+                    usage.level = t.level(usage.percent);
+                }
+            }
+
+            Ok(usages)
+        };
+
+        tx.send(Message::DiskUsage(update()?)).unwrap();
+
+        let is_discover = match self.mounts {
+            Mounts::Discover => true,
+            _ => false,
+        };
 
-        Ok(thread::spawn(move || {
+        let handle = thread::spawn(move || {
             loop {
-                if let Err(e) = get_usage(mountpoints.clone()).map_err(|e| e.to_string())
+                if let Err(e) = update().map_err(|e| e.to_string())
                     .and_then(|usage| tx.send(Message::DiskUsage(usage))
                               .map_err(|e| e.to_string())) {
-                        println!("volume sensor ERROR: {}", e);
+                        println!("diskusage sensor ERROR: {}", e);
                     }
 
                 thread::park_timeout(iv);
             }
-        }))
+        });
+
+        // wake the sensor whenever the mount table changes.
+        if is_discover {
+            let sensor = handle.thread().clone();
+            thread::spawn(move || {
+                if let Err(e) = watch_mounts(|| sensor.unpark()) {
+                    println!("diskusage mount watch ERROR: {}", e);
+                }
+            });
+        }
+
+        Ok(handle)
+    }
+}
+
+fn get_usage(mountpoints: &[String]) -> Vec<MountUsage> {
+    let mut usages = Vec::new();
+
+    for mp in mountpoints {
+        match statvfs(mp) {
+            Ok(usage) => usages.push(usage),
+            Err(e) => println!("diskusage '{}' ERROR: {}", mp, e),
+        }
     }
+
+    usages
 }
 
-const DF_USAGE_COL: usize = 4;
-const DF_MOUNT_COL: usize = 5;
+pub fn statvfs(mountpoint: &str) -> Result<MountUsage, BarError> {
+    let path = CString::new(mountpoint)
+        .map_err(|e| BarError::Bar(e.to_string()))?;
+
+    let mut st: libc::statvfs = unsafe { mem::zeroed() };
+    if unsafe { libc::statvfs(path.as_ptr(), &mut st) } != 0 {
+        return Err(BarError::Io(io::Error::last_os_error()));
+    }
+
+    let frsize = st.f_frsize as u64;
+    let total = st.f_blocks as u64 * frsize;
+    let free = st.f_bfree as u64 * frsize;
+    let available = st.f_bavail as u64 * frsize;
+    let used = total - free;
+
+    let inodes_total = st.f_files as u64;
+    let inodes_used = inodes_total - st.f_ffree as u64;
 
-fn get_usage(mountpoints: Vec<String>) -> Result<String, BarError> {
-    let output = Command::new("df")
-        .arg("-h")
-        .output()?;
+    Ok(MountUsage {
+        mountpoint: mountpoint.to_owned(),
+        total: total,
+        used: used,
+        available: available,
 
-    if !output.status.success() {
-        return Err(
-            BarError::Bar(
-                format!(
-                    "'DiskUsage df' ERROR: {}", from_utf8(&output.stderr)?)));
+        // like df, space reserved for root doesn't count toward the total.
+        percent: percent(used, used + available),
+        inodes_total: inodes_total,
+        inodes_used: inodes_used,
+        inodes_percent: percent(inodes_used, inodes_total),
+        level: Level::Normal,
+    })
+}
+
+fn percent(part: u64, whole: u64) -> f64 {
+    if whole == 0 {
+        return 0.;
     }
+    part as f64 / whole as f64 * 100.
+}
+
+fn read_mountinfo() -> Result<Vec<Mount>, BarError> {
+    let mut contents = String::new();
+    File::open(MOUNTINFO_PATH)?.read_to_string(&mut contents)?;
+    Ok(parse_mountinfo(&contents))
+}
 
-    let extract_mounts = |s: &str| -> String {
+// Parse the contents of /proc/self/mountinfo, dropping pseudo filesystems
+// and any mount of a device that has already been seen (bind mounts).
+// see https://www.kernel.org/doc/Documentation/filesystems/proc.txt
+pub fn parse_mountinfo(contents: &str) -> Vec<Mount> {
+    let mut mounts: Vec<Mount> = Vec::new();
 
-        let mut usages = Vec::new();
-        let lines: Vec<&str> = s.split("\n").collect();
+    for line in contents.lines() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
 
-        for mp in mountpoints {
-            for line in &lines {
-                let fields: Vec<&str> = line.split_whitespace().collect();
-                if fields.len() > DF_MOUNT_COL &&
-                    fields[DF_MOUNT_COL] == mp {
-                        usages.push(format!("{} {}", mp, fields[DF_USAGE_COL]));
-                    }
+        // optional fields end with a lone "-" followed by fstype and source.
+        let sep = match fields.iter().position(|&f| f == "-") {
+            Some(i) if i >= 6 && fields.len() > i + 2 => i,
+            _ => continue,
+        };
+
+        let mount = Mount {
+            device: fields[2].to_owned(),
+            mountpoint: unescape(fields[4]),
+            fstype: fields[sep + 1].to_owned(),
+            source: unescape(fields[sep + 2]),
+        };
+
+        let base_fstype = mount.fstype.split('.').next().unwrap_or("");
+        if PSEUDO_FS.contains(&base_fstype) ||
+            VIRTUAL_FUSE.contains(&mount.fstype.as_ref()) {
+            continue;
+        }
+
+        if mounts.iter().any(|m| m.device == mount.device) {
+            continue;
+        }
+
+        mounts.push(mount);
+    }
+
+    mounts
+}
+
+// mountinfo escapes space, tab, newline and backslash as \ooo octal.
+fn unescape(field: &str) -> String {
+    let bytes = field.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] == b'\\' && i + 3 < bytes.len() {
+            let code = &bytes[i + 1..i + 4];
+            if code.iter().all(|&b| b >= b'0' && b <= b'7') {
+                out.push(code.iter().fold(0u32, |acc, &b| acc * 8 + (b - b'0') as u32) as u8);
+                i += 4;
+                continue;
             }
         }
+        out.push(bytes[i]);
+        i += 1;
+    }
+
+    String::from_utf8_lossy(&out).into_owned()
+}
 
-        return usages.join("  ");
-    };
+// The kernel flags /proc/self/mountinfo with POLLPRI whenever the mount
+// table changes.
+fn watch_mounts<F: Fn()>(on_change: F) -> Result<(), BarError> {
+    let f = File::open(MOUNTINFO_PATH)?;
 
+    loop {
+        let mut fds = libc::pollfd {
+            fd: f.as_raw_fd(),
+            events: libc::POLLPRI | libc::POLLERR,
+            revents: 0,
+        };
 
-    from_utf8(&output.stdout)
-        .map(extract_mounts)
-        .map(|s| s.trim().to_owned())
-        .map_err(|e| BarError::Utf8(e))
+        if unsafe { libc::poll(&mut fds, 1, -1) } < 0 {
+            let err = io::Error::last_os_error();
+            if err.kind() == io::ErrorKind::Interrupted {
+                continue;
+            }
+            return Err(BarError::Io(err));
+        }
+
+        on_change();
+    }
 }
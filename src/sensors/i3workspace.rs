@@ -2,11 +2,24 @@ use i3ipc::I3Connection;
 use i3ipc::I3EventListener;
 use i3ipc::Subscription;
 use i3ipc::event::Event;
+use i3ipc::reply::Workspace;
 use message::Message;
 use error::BarError;
+use r3ipc::R3Funcs;
 use sensors::{Sensor, SensorResult};
+use serde_json as json;
+use std::env;
 use std::sync::mpsc::{Sender};
 use std::thread;
+use unix_socket::UnixStream;
+
+// i3 IPC message and event types, see https://i3wm.org/docs/ipc.html
+const RUN_COMMAND: u32 = 0;
+const GET_WORKSPACES: u32 = 1;
+const SUBSCRIBE: u32 = 2;
+const WORKSPACE_EVENT: u32 = 0;
+const MODE_EVENT: u32 = 2;
+const WINDOW_EVENT: u32 = 3;
 
 pub struct I3Workspace {}
 
@@ -18,6 +31,12 @@ impl I3Workspace {
     pub fn change_workspace(workspace: String, output: String) -> Result<(), BarError> {
         let cmd = format!("workspace {}, move workspace to {}", workspace, output);
         println!("running {}", cmd);
+
+        if let Some(socket) = socket_path() {
+            let mut stream = UnixStream::connect(&socket)?;
+            return check_success(&request(&mut stream, RUN_COMMAND, &cmd)?);
+        }
+
         let mut connection = I3Connection::connect()?;
         let outcomes = connection.command(&cmd).ok().expect("failed to send command").outcomes;
 
@@ -37,6 +56,10 @@ impl I3Workspace {
 impl Sensor for I3Workspace {
     fn run(&self, tx: Sender<Message>) -> SensorResult {
 
+        if let Some(socket) = socket_path() {
+            return run_on_socket(socket, tx);
+        }
+
         // send a snapshot of current workspace immediately
         let mut connection = I3Connection::connect()?;
         let w = connection.get_workspaces()?;
@@ -65,3 +88,100 @@ impl Sensor for I3Workspace {
         }))
     }
 }
+
+// sway speaks i3's IPC on the socket in $SWAYSOCK and i3 sets $I3SOCK.
+// Without either the i3ipc crate asks `i3 --get-socketpath`.
+pub fn socket_path() -> Option<String> {
+    ["SWAYSOCK", "I3SOCK"].iter()
+        .filter_map(|var| env::var(var).ok())
+        .find(|path| !path.is_empty())
+}
+
+// Same as the i3ipc path but over a socket we found ourselves.
+fn run_on_socket(socket: String, tx: Sender<Message>) -> SensorResult {
+    tx.send(Message::Workspaces(get_workspaces(&socket)?)).map_err(|e| e.to_string())?;
+
+    let mut events = UnixStream::connect(&socket)?;
+    check_success(&request(&mut events, SUBSCRIBE, r#"["workspace","mode","window"]"#)?)?;
+
+    Ok(thread::spawn(move || {
+        loop {
+            let (msgtype, payload) = events.receive_i3_message()?;
+
+            // strip the highest order bit marking it as an event.
+            match (msgtype << 1) >> 1 {
+                WORKSPACE_EVENT | WINDOW_EVENT => {
+                    tx.send(Message::Workspaces(get_workspaces(&socket)?))
+                        .map_err(|e| e.to_string())?;
+                }
+                MODE_EVENT => {
+                    tx.send(Message::I3Mode(parse_mode(&payload)?)).map_err(|e| e.to_string())?;
+                }
+                _ => (),
+            }
+        }
+    }))
+}
+
+fn request(stream: &mut UnixStream, msgtype: u32, payload: &str) -> Result<String, BarError> {
+    stream.send_i3_message(msgtype, payload)?;
+    Ok(stream.receive_i3_message()?.1)
+}
+
+pub fn get_workspaces(socket: &str) -> Result<Vec<Workspace>, BarError> {
+    let mut stream = UnixStream::connect(socket)?;
+    parse_workspaces(&request(&mut stream, GET_WORKSPACES, "")?)
+}
+
+// A GET_WORKSPACES reply, into the workspaces i3ipc would have given us.
+pub fn parse_workspaces(payload: &str) -> Result<Vec<Workspace>, BarError> {
+    let value: json::Value = json::from_str(payload)?;
+    let workspaces = value.as_array()
+        .ok_or(BarError::Bar("workspaces reply not an array".to_owned()))?;
+
+    workspaces.iter().map(|w| {
+        let err = || BarError::Bar(format!("unexpected workspace {}", w));
+        let int = |v: Option<&json::Value>| v.and_then(|v| v.as_i64()).map(|i| i as i32);
+        let flag = |key: &str| w.get(key).and_then(|v| v.as_bool()).unwrap_or(false);
+        let rect = w.get("rect").ok_or_else(&err)?;
+
+        Ok(Workspace {
+            num: int(w.get("num")).unwrap_or(-1),
+            name: w.get("name").and_then(|n| n.as_str()).ok_or_else(&err)?.to_owned(),
+            visible: flag("visible"),
+            focused: flag("focused"),
+            urgent: flag("urgent"),
+            rect: (int(rect.get("x")).unwrap_or(0),
+                   int(rect.get("y")).unwrap_or(0),
+                   int(rect.get("width")).unwrap_or(0),
+                   int(rect.get("height")).unwrap_or(0)),
+            output: w.get("output").and_then(|o| o.as_str()).ok_or_else(&err)?.to_owned(),
+        })
+    }).collect()
+}
+
+// {"change": "resize", "pango_markup": false}
+pub fn parse_mode(payload: &str) -> Result<String, BarError> {
+    let value: json::Value = json::from_str(payload)?;
+    value.get("change").and_then(|c| c.as_str()).map(|c| c.to_owned())
+        .ok_or(BarError::Bar(format!("unexpected mode event {}", payload)))
+}
+
+// Replies to commands and subscriptions, [{"success": true}] or
+// {"success": true}.
+fn check_success(payload: &str) -> Result<(), BarError> {
+    let value: json::Value = json::from_str(payload)?;
+    let outcomes = match value.as_array() {
+        Some(outcomes) => outcomes.clone(),
+        None => vec![value],
+    };
+
+    for outcome in outcomes {
+        if !outcome.get("success").and_then(|s| s.as_bool()).unwrap_or(false) {
+            let error = outcome.get("error").and_then(|e| e.as_str()).unwrap_or("unknown reason");
+            return Err(BarError::Bar(format!("i3 ipc: {}", error)));
+        }
+    }
+
+    Ok(())
+}
@@ -1,15 +1,20 @@
 use error::BarError;
-use message::{Message, WebpackInfo};
+use message::{Message, Screenshot, WebpackInfo};
 use r3ipc::{R3Funcs, R3_UNIX_SOCK, self};
 use sensors::{Sensor, SensorResult};
+use serde_json as json;
 use std::{io, fs};
 use std::path::Path;
 use std::str::FromStr;
 use std::sync::mpsc;
+use std::time::Duration;
 use std::{thread};
 use unix_socket::{UnixStream, UnixListener};
 
 
+// How long a screenshot has to be saved before the reply gives up on it.
+const SCREENSHOT_TIMEOUT_MS: u64 = 5000;
+
 pub struct Ipc {
     pub socket_path: String,
 }
@@ -59,12 +64,26 @@ fn handle_client(mut stream: UnixStream, tx: mpsc::Sender<Message>) {
                 // strip the highest order bit indicating it's an event.
                 let msgtype = (msgint << 1) >> 1;
 
-                let message = to_message(msgtype, payload);
+                let (done_tx, done_rx) = mpsc::channel();
+                let message = to_message(msgtype, payload, done_tx);
                 let message_err = message.is_err();
                 let reply = match message {
                     Ok(msg) => {
                         tx.send(msg).unwrap();
-                        stream.send_i3_message(r3ipc::REPLY, &reply_ok())
+
+                        // screenshots are answered once the bars have saved
+                        // them, or failed to.
+                        let done = if msgtype == r3ipc::SCREENSHOT {
+                            done_rx.recv_timeout(Duration::from_millis(SCREENSHOT_TIMEOUT_MS))
+                                .unwrap_or(Err("screenshot not taken".to_owned()))
+                        } else {
+                            Ok(())
+                        };
+
+                        match done {
+                            Ok(()) => stream.send_i3_message(r3ipc::REPLY, &reply_ok()),
+                            Err(e) => stream.send_i3_message(r3ipc::REPLY, &reply_err(e)),
+                        }
                     },
                     Err(e) => {
                         let err_msg = e.to_string().clone();
@@ -101,13 +120,17 @@ fn handle_client(mut stream: UnixStream, tx: mpsc::Sender<Message>) {
     }
 }
 
-fn to_message(msgtype: u32, payload: String) -> Result<Message, BarError> {
+fn to_message(msgtype: u32,
+              payload: String,
+              done: mpsc::Sender<Result<(), String>>) -> Result<Message, BarError> {
     match msgtype {
         c @ 0...r3ipc::RESERVED => Err(BarError::Bar(
             format!("r3ipc: reserved code range {}", c))),
         r3ipc::WEBPACK => Ok(Message::Webpack(WebpackInfo::from_str(&payload)?)),
-        r3ipc::UNPARK => Ok(Message::Unpark),
+        r3ipc::UNPARK => Ok(Message::Unpark(
+            if payload.is_empty() { None } else { Some(payload) })),
         r3ipc::TICKER => Ok(Message::Ticker(payload)),
+        r3ipc::SCREENSHOT => Ok(Message::Screenshot(Screenshot::from_str(&payload)?, done)),
         _ => Err(BarError::Bar(
             format!("r3ipc: msgtype '{}' not implemented", msgtype))),
     }
@@ -122,5 +145,6 @@ fn reply_ok<'a>() -> String {
 
 // see https://i3wm.org/docs/ipc.html#_command_reply
 fn reply_err<'a>(errmsg: String) -> String {
-    return format!("[{{ \"success\": false, \"error\": \"{}\" }}]", errmsg);
+    // errors can hold paths, so quote them properly.
+    return format!("[{{ \"success\": false, \"error\": {} }}]", json::Value::String(errmsg));
 }
@@ -0,0 +1,156 @@
+use error::BarError;
+use libc;
+use message::Message;
+use sensors::{Level, Sensor, SensorResult};
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+static LOADAVG_PATH: &'static str = "/proc/loadavg";
+static UPTIME_PATH: &'static str = "/proc/uptime";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoadInfo {
+    pub one: f64,
+    pub five: f64,
+    pub fifteen: f64,
+    // tasks currently runnable and in existence.
+    pub running: u32,
+    pub total: u32,
+    pub uptime: Duration,
+    pub cpus: u32,
+    pub level: Level,
+}
+
+impl LoadInfo {
+    pub fn new() -> LoadInfo {
+        LoadInfo {
+            one: 0.,
+            five: 0.,
+            fifteen: 0.,
+            running: 0,
+            total: 0,
+            uptime: Duration::from_secs(0),
+            cpus: 1,
+            level: Level::Normal,
+        }
+    }
+
+    // One minute load per online CPU, so 1.0 means every CPU is busy.
+    pub fn per_cpu(&self) -> f64 {
+        self.one / self.cpus.max(1) as f64
+    }
+}
+
+pub struct LoadAvg {
+    interval: Duration,
+    warning: f64,
+    critical: f64,
+    loadavg_path: PathBuf,
+    uptime_path: PathBuf,
+}
+
+impl LoadAvg {
+    pub fn new(interval: Duration) -> LoadAvg {
+        LoadAvg {
+            interval: interval,
+            warning: 1.,
+            critical: 2.,
+            loadavg_path: PathBuf::from(LOADAVG_PATH),
+            uptime_path: PathBuf::from(UPTIME_PATH),
+        }
+    }
+
+    // Thresholds are load per CPU, see `LoadInfo::per_cpu`.
+    pub fn with_thresholds(mut self, warning: f64, critical: f64) -> LoadAvg {
+        self.warning = warning;
+        self.critical = critical;
+        self
+    }
+
+    pub fn with_paths<P, Q>(mut self, loadavg: P, uptime: Q) -> LoadAvg
+        where P: AsRef<Path>, Q: AsRef<Path>
+    {
+        self.loadavg_path = loadavg.as_ref().to_path_buf();
+        self.uptime_path = uptime.as_ref().to_path_buf();
+        self
+    }
+}
+
+impl Sensor for LoadAvg {
+    fn run(&self, tx: mpsc::Sender<Message>) -> SensorResult {
+
+        let iv = self.interval;
+        let warning = self.warning;
+        let critical = self.critical;
+        let loadavg_path = self.loadavg_path.clone();
+        let uptime_path = self.uptime_path.clone();
+
+        let update = move || -> Result<LoadInfo, BarError> {
+            let mut info = parse_loadavg(&read_file(&loadavg_path)?)?;
+            info.uptime = parse_uptime(&read_file(&uptime_path)?)?;
+            info.cpus = online_cpus();
+            info.level = Level::of(info.per_cpu(), warning, critical);
+            Ok(info)
+        };
+
+        tx.send(Message::LoadAvg(update()?)).unwrap();
+
+        Ok(thread::spawn(move || {
+            loop {
+                thread::park_timeout(iv);
+
+                if let Err(e) = update().map_err(|e| e.to_string())
+                    .and_then(|info| tx.send(Message::LoadAvg(info))
+                              .map_err(|e| e.to_string())) {
+                        println!("loadavg sensor ERROR: {}", e);
+                    }
+            }
+        }))
+    }
+}
+
+// 0.52 0.58 0.59 2/1127 31337
+pub fn parse_loadavg(contents: &str) -> Result<LoadInfo, BarError> {
+    let err = || BarError::Bar(format!("/proc/loadavg: unexpected '{}'", contents.trim()));
+
+    let fields: Vec<&str> = contents.split_whitespace().collect();
+    if fields.len() < 4 {
+        return Err(err());
+    }
+
+    let load = |s: &str| s.parse::<f64>().map_err(|_| err());
+    let mut tasks = fields[3].splitn(2, '/')
+        .map(|s| s.parse::<u32>().map_err(|_| err()));
+
+    let mut info = LoadInfo::new();
+    info.one = load(fields[0])?;
+    info.five = load(fields[1])?;
+    info.fifteen = load(fields[2])?;
+    info.running = tasks.next().unwrap_or(Err(err()))?;
+    info.total = tasks.next().unwrap_or(Err(err()))?;
+    Ok(info)
+}
+
+// 350735.47 234388.90
+// seconds since boot followed by the idle time summed over CPUs.
+pub fn parse_uptime(contents: &str) -> Result<Duration, BarError> {
+    contents.split_whitespace().next()
+        .and_then(|s| s.parse::<f64>().ok())
+        .map(|secs| Duration::from_secs(secs as u64))
+        .ok_or(BarError::Bar(format!("/proc/uptime: unexpected '{}'", contents.trim())))
+}
+
+fn read_file(path: &Path) -> Result<String, BarError> {
+    let mut contents = String::new();
+    File::open(path)?.read_to_string(&mut contents)?;
+    Ok(contents)
+}
+
+fn online_cpus() -> u32 {
+    let n = unsafe { libc::sysconf(libc::_SC_NPROCESSORS_ONLN) };
+    if n < 1 { 1 } else { n as u32 }
+}
@@ -0,0 +1,199 @@
+use error::BarError;
+use message::Message;
+use sensors::{Sensor, SensorResult};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+static MEMINFO_PATH: &'static str = "/proc/meminfo";
+static BLOCK_PATH: &'static str = "/sys/block";
+
+// Sizes are in bytes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MemInfo {
+    pub total: u64,
+    pub used: u64,
+    pub available: u64,
+    pub cached: u64,
+    pub swap_total: u64,
+    pub swap_used: u64,
+    pub zram: Option<Zram>,
+    pub urgent: bool,
+}
+
+impl MemInfo {
+    pub fn new() -> MemInfo {
+        MemInfo {
+            total: 0,
+            used: 0,
+            available: 0,
+            cached: 0,
+            swap_total: 0,
+            swap_used: 0,
+            zram: None,
+            urgent: false,
+        }
+    }
+}
+
+// Summed over every zram device. `original` is the data stored and `used`
+// the memory it takes up once compressed, out of `size` uncompressed bytes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Zram {
+    pub original: u64,
+    pub used: u64,
+    pub size: u64,
+}
+
+pub struct Memory {
+    interval: Duration,
+    urgent_below: Option<u64>,
+    meminfo_path: PathBuf,
+    block_path: PathBuf,
+}
+
+impl Memory {
+    pub fn new(interval: Duration) -> Memory {
+        Memory {
+            interval: interval,
+            urgent_below: None,
+            meminfo_path: PathBuf::from(MEMINFO_PATH),
+            block_path: PathBuf::from(BLOCK_PATH),
+        }
+    }
+
+    // Mark the reading urgent once available memory drops below `bytes`.
+    pub fn with_urgent_below(mut self, bytes: u64) -> Memory {
+        self.urgent_below = Some(bytes);
+        self
+    }
+
+    pub fn with_paths<P, Q>(mut self, meminfo: P, block: Q) -> Memory
+        where P: AsRef<Path>, Q: AsRef<Path>
+    {
+        self.meminfo_path = meminfo.as_ref().to_path_buf();
+        self.block_path = block.as_ref().to_path_buf();
+        self
+    }
+}
+
+impl Sensor for Memory {
+    fn run(&self, tx: mpsc::Sender<Message>) -> SensorResult {
+
+        let iv = self.interval;
+        let urgent_below = self.urgent_below;
+        let meminfo_path = self.meminfo_path.clone();
+        let block_path = self.block_path.clone();
+
+        let update = move || -> Result<MemInfo, BarError> {
+            let mut contents = String::new();
+            File::open(&meminfo_path)?.read_to_string(&mut contents)?;
+
+            let mut info = parse_meminfo(&contents)?;
+            info.zram = read_zram(&block_path);
+            info.urgent = urgent_below.map_or(false, |b| info.available < b);
+            Ok(info)
+        };
+
+        tx.send(Message::Memory(update()?)).unwrap();
+
+        Ok(thread::spawn(move || {
+            loop {
+                thread::park_timeout(iv);
+
+                if let Err(e) = update().map_err(|e| e.to_string())
+                    .and_then(|info| tx.send(Message::Memory(info))
+                              .map_err(|e| e.to_string())) {
+                        println!("memory sensor ERROR: {}", e);
+                    }
+            }
+        }))
+    }
+}
+
+// MemTotal:       16314460 kB
+// MemAvailable:    9120944 kB
+pub fn parse_meminfo(contents: &str) -> Result<MemInfo, BarError> {
+    let mut fields = HashMap::new();
+
+    for line in contents.lines() {
+        let mut split = line.splitn(2, ':');
+        if let (Some(key), Some(value)) = (split.next(), split.next()) {
+            let kb = value.split_whitespace().next()
+                .and_then(|v| v.parse::<u64>().ok());
+            if let Some(kb) = kb {
+                fields.insert(key.trim(), kb * 1024);
+            }
+        }
+    }
+
+    let field = |key: &str| fields.get(key).map(|&v| v)
+        .ok_or(BarError::Bar(format!("meminfo: missing {}", key)));
+
+    let total = field("MemTotal")?;
+    let available = field("MemAvailable")?;
+    let swap_total = field("SwapTotal")?;
+    let swap_free = field("SwapFree")?;
+
+    // reclaimable slab is cache as far as the user is concerned.
+    let cached = field("Cached")? + field("Buffers").unwrap_or(0) +
+        field("SReclaimable").unwrap_or(0);
+
+    Ok(MemInfo {
+        total: total,
+        used: total.saturating_sub(available),
+        available: available,
+        cached: cached,
+        swap_total: swap_total,
+        swap_used: swap_total.saturating_sub(swap_free),
+        zram: None,
+        urgent: false,
+    })
+}
+
+// zram isn't broken out in /proc/meminfo so read each device's mm_stat.
+// see https://www.kernel.org/doc/Documentation/blockdev/zram.txt
+pub fn read_zram<P: AsRef<Path>>(block_path: P) -> Option<Zram> {
+    let entries = match fs::read_dir(block_path) {
+        Ok(entries) => entries,
+        Err(_) => return None,
+    };
+
+    let mut found = false;
+    let mut zram = Zram{original: 0, used: 0, size: 0};
+    for entry in entries.filter_map(|e| e.ok()) {
+        if !entry.file_name().to_string_lossy().starts_with("zram") {
+            continue;
+        }
+
+        let mut contents = String::new();
+        if File::open(entry.path().join("mm_stat"))
+            .and_then(|mut f| f.read_to_string(&mut contents)).is_err() {
+                continue;
+            }
+
+        // orig_data_size compr_data_size mem_used_total ...
+        let stats: Vec<u64> = contents.split_whitespace()
+            .filter_map(|s| s.parse::<u64>().ok())
+            .collect();
+        if stats.len() < 3 {
+            continue;
+        }
+
+        let mut disksize = String::new();
+        if File::open(entry.path().join("disksize"))
+            .and_then(|mut f| f.read_to_string(&mut disksize)).is_ok() {
+                zram.size += disksize.trim().parse::<u64>().unwrap_or(0);
+            }
+
+        found = true;
+        zram.original += stats[0];
+        zram.used += stats[2];
+    }
+
+    if found { Some(zram) } else { None }
+}
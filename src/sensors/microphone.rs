@@ -0,0 +1,109 @@
+use error::BarError;
+use regex::Regex;
+use sensors;
+use message::Message;
+use std::process::Command;
+use std::str::from_utf8;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+static DEFAULT_SOURCE: &'static str = "@DEFAULT_SOURCE@";
+
+#[derive(Debug)]
+pub struct Microphone {
+    interval: Duration,
+}
+
+#[derive(Clone, Debug)]
+pub struct MicStatus {
+    pub volume: f64,
+    pub muted: bool,
+    pub recording: bool,
+}
+
+impl MicStatus {
+    pub fn new() -> MicStatus {
+        MicStatus {
+            volume: 0.,
+            muted: false,
+            recording: false,
+        }
+    }
+}
+
+impl Microphone {
+
+    pub fn new(interval: Duration) -> Self {
+        Microphone{interval: interval}
+    }
+
+    pub fn toggle_mute() -> Result<(), BarError> {
+        pactl(&["set-source-mute", DEFAULT_SOURCE, "toggle"]).map(|_| ())
+    }
+}
+
+impl sensors::Sensor for Microphone {
+    fn run(&self, tx: mpsc::Sender<Message>) -> sensors::SensorResult {
+
+        let iv = self.interval;
+        tx.send(Message::Microphone(get_status()?)).unwrap();
+
+        Ok(thread::spawn(move || {
+            loop {
+                if let Err(e) = get_status().map_err(|e| e.to_string())
+                    .and_then(|status| tx.send(Message::Microphone(status))
+                              .map_err(|e| e.to_string())) {
+                        println!("microphone sensor ERROR: {}", e);
+                    }
+
+                thread::park_timeout(iv);
+            }
+        }))
+    }
+}
+
+
+fn get_status() -> Result<MicStatus, BarError> {
+
+    // "Volume: front-left: 32768 /  50% / -18.06 dB,   front-right: ..."
+    // take the first channel as representative of the source.
+    let volume_out = pactl(&["get-source-volume", DEFAULT_SOURCE])?;
+    let re = Regex::new(r"(\d+)%").unwrap();
+    let volume = re.captures(&volume_out)
+        .and_then(|captures| captures.get(1))
+        .and_then(|m| m.as_str().parse::<f64>().ok())
+        .ok_or(BarError::Bar(
+            format!("'pactl get-source-volume' unexpected output: {}", volume_out)))?;
+
+    // "Mute: yes"
+    let mute_out = pactl(&["get-source-mute", DEFAULT_SOURCE])?;
+    let muted = mute_out.ends_with("yes");
+
+    // every application capturing audio shows up as a source output.
+    let outputs = pactl(&["list", "short", "source-outputs"])?;
+    let recording = outputs.lines().any(|l| !l.trim().is_empty());
+
+    Ok(MicStatus {
+        volume: volume,
+        muted: muted,
+        recording: recording,
+    })
+}
+
+fn pactl(args: &[&str]) -> Result<String, BarError> {
+    let output = Command::new("pactl")
+        .args(args)
+        .output()?;
+
+    if !output.status.success() {
+        return Err(
+            BarError::Bar(
+                format!("'pactl {}' ERROR: {}",
+                        args.join(" "), from_utf8(&output.stderr)?)));
+    }
+
+    from_utf8(&output.stdout)
+        .map(|s| s.trim().to_string())
+        .map_err(|e| BarError::Utf8(e))
+}
@@ -1,5 +1,8 @@
 use error::BarError;
 use message::Message;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
 use std::sync::mpsc;
 use std::thread;
 
@@ -10,9 +13,53 @@ pub mod wifi;
 pub mod ipc;
 pub mod volume;
 pub mod diskusage;
+pub mod diskio;
+pub mod microphone;
+pub mod backlight;
+pub mod command;
+pub mod statusline;
+pub mod wired;
+pub mod netspeed;
+pub mod cpu;
+pub mod memory;
+pub mod thermal;
+pub mod cpufreq;
+pub mod loadavg;
+
+// How alarming a reading is, for sensors with warning and critical
+// thresholds. Ordered so the worst of several readings is their max.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Normal,
+    Warning,
+    Critical,
+}
+
+impl Level {
+    // Where `value` falls against thresholds that are crossed going up.
+    pub fn of(value: f64, warning: f64, critical: f64) -> Level {
+        if value >= critical {
+            Level::Critical
+        } else if value >= warning {
+            Level::Warning
+        } else {
+            Level::Normal
+        }
+    }
+}
 
 type SensorResult = Result<thread::JoinHandle<Result<(), BarError>>, BarError>;
 
 pub trait Sensor {
     fn run(&self, tx: mpsc::Sender<Message>) -> SensorResult;
 }
+
+// A sysfs attribute without its trailing newline. Attributes come and go
+// with the hardware and driver so a missing one is None rather than an error.
+pub fn read_sys_file<P: AsRef<Path>>(path: P) -> Option<String> {
+    let mut contents = String::new();
+    if File::open(path).and_then(|mut f| f.read_to_string(&mut contents)).is_err() {
+        return None;
+    }
+    Some(contents.trim().to_owned())
+}
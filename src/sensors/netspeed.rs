@@ -0,0 +1,173 @@
+use error::BarError;
+use message::Message;
+use sensors::{Sensor, SensorResult};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+static NET_DEV_PATH: &'static str = "/proc/net/dev";
+
+// How far below 2^32 a counter may be and still be taken to have wrapped.
+// Generous enough for a 1 Gbit/s link between slow samples.
+const WRAP_WINDOW: u64 = 1 << 30;
+
+// Byte counters for one interface as read from /proc/net/dev.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Counters {
+    pub rx_bytes: u64,
+    pub tx_bytes: u64,
+}
+
+// Bytes per second.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InterfaceRate {
+    pub name: String,
+    pub rx: f64,
+    pub tx: f64,
+}
+
+pub struct NetSpeed {
+    interval: Duration,
+    interfaces: Vec<String>,
+    path: PathBuf,
+}
+
+impl NetSpeed {
+    // An empty list of interfaces samples everything except loopback.
+    pub fn new(interval: Duration, interfaces: Vec<String>) -> NetSpeed {
+        NetSpeed {
+            interval: interval,
+            interfaces: interfaces,
+            path: PathBuf::from(NET_DEV_PATH),
+        }
+    }
+
+    pub fn with_path<P: AsRef<Path>>(mut self, path: P) -> NetSpeed {
+        self.path = path.as_ref().to_path_buf();
+        self
+    }
+}
+
+impl Sensor for NetSpeed {
+    fn run(&self, tx: mpsc::Sender<Message>) -> SensorResult {
+
+        let iv = self.interval;
+        let path = self.path.clone();
+        let interfaces = self.interfaces.clone();
+
+        let mut last = read_net_dev(&path, &interfaces)?;
+        let mut last_time = Instant::now();
+
+        Ok(thread::spawn(move || {
+            loop {
+                thread::park_timeout(iv);
+
+                match read_net_dev(&path, &interfaces) {
+                    Ok(next) => {
+                        let now = Instant::now();
+                        let speeds = rates(&last, &next, now.duration_since(last_time));
+                        last = next;
+                        last_time = now;
+
+                        if let Err(e) = tx.send(Message::NetSpeed(speeds)) {
+                            println!("netspeed sensor ERROR: {}", e);
+                        }
+                    }
+                    Err(e) => println!("netspeed sensor ERROR: {}", e),
+                }
+            }
+        }))
+    }
+}
+
+pub fn read_net_dev<P: AsRef<Path>>(path: P, interfaces: &[String])
+                                    -> Result<HashMap<String, Counters>, BarError> {
+    let mut contents = String::new();
+    File::open(path)?.read_to_string(&mut contents)?;
+
+    let mut counters = parse_net_dev(&contents)?;
+    if interfaces.is_empty() {
+        counters.remove("lo");
+    } else {
+        counters.retain(|name, _| interfaces.contains(name));
+    }
+
+    Ok(counters)
+}
+
+// Inter-|   Receive                            ...|  Transmit
+//  face |bytes    packets errs drop fifo frame ...|bytes    packets ...
+//   eth0: 1234     12      0    0    0    0    ...  5678     34      ...
+pub fn parse_net_dev(contents: &str) -> Result<HashMap<String, Counters>, BarError> {
+    let mut counters = HashMap::new();
+
+    // the first two lines are headers.
+    for line in contents.lines().skip(2) {
+        let mut split = line.splitn(2, ':');
+        let name = match split.next() {
+            Some(name) => name.trim(),
+            None => continue,
+        };
+        let fields: Vec<&str> = match split.next() {
+            Some(rest) => rest.split_whitespace().collect(),
+            None => continue,
+        };
+
+        if fields.len() < 16 {
+            return Err(BarError::Bar(format!("/proc/net/dev: short line for '{}'", name)));
+        }
+
+        let parse = |f: &str| f.parse::<u64>()
+            .map_err(|e| BarError::Bar(format!("/proc/net/dev '{}': {}", name, e)));
+
+        counters.insert(name.to_owned(), Counters {
+            rx_bytes: parse(fields[0])?,
+            tx_bytes: parse(fields[8])?,
+        });
+    }
+
+    Ok(counters)
+}
+
+// Rates for every interface present in `next`. Interfaces that are new
+// since `prev` report zero until the following sample.
+pub fn rates(prev: &HashMap<String, Counters>,
+             next: &HashMap<String, Counters>,
+             elapsed: Duration) -> Vec<InterfaceRate> {
+
+    let secs = elapsed.as_secs() as f64 + elapsed.subsec_nanos() as f64 * 1e-9;
+
+    let mut speeds: Vec<InterfaceRate> = next.iter().map(|(name, n)| {
+        let (rx, tx) = match prev.get(name) {
+            Some(p) if secs > 0. => (
+                delta(p.rx_bytes, n.rx_bytes) as f64 / secs,
+                delta(p.tx_bytes, n.tx_bytes) as f64 / secs,
+            ),
+            _ => (0., 0.),
+        };
+
+        InterfaceRate{name: name.clone(), rx: rx, tx: tx}
+    }).collect();
+
+    speeds.sort_by(|a, b| a.name.cmp(&b.name));
+    speeds
+}
+
+// Counters are 32 bits wide on some drivers and wrap. Only a counter that
+// was close to 2^32 is taken to have wrapped, anything else going backwards
+// means the interface was recreated so count from zero.
+fn delta(prev: u64, next: u64) -> u64 {
+    let max = u32::max_value() as u64;
+
+    if next >= prev {
+        next - prev
+    } else if prev <= max && prev > max - WRAP_WINDOW {
+        next + (max + 1) - prev
+    } else {
+        next
+    }
+}
@@ -0,0 +1,123 @@
+use error::BarError;
+use i3bar::{self, Block, ClickEvent, Header};
+use message::Message;
+use serde_json as json;
+use sensors::{Sensor, SensorResult};
+use sensors::command::respawn;
+use std::io::Write;
+use std::process::ChildStdin;
+use std::sync::{Arc, Mutex, mpsc};
+use std::thread;
+
+// Where clicks go, present while the producer runs.
+struct ClickPipe {
+    stdin: ChildStdin,
+    // set once the producer's header asks for clicks.
+    wanted: bool,
+    first: bool,
+}
+
+impl From<ChildStdin> for ClickPipe {
+    fn from(stdin: ChildStdin) -> ClickPipe {
+        ClickPipe{stdin: stdin, wanted: false, first: true}
+    }
+}
+
+// Runs an i3bar protocol producer such as i3status or i3status-rust and
+// reports each status line it prints. Producers that don't print a header
+// are treated as plain text, one status per line, as i3bar does.
+pub struct StatusLine {
+    name: String,
+    command: String,
+    clicks: Arc<Mutex<Option<ClickPipe>>>,
+}
+
+impl StatusLine {
+    pub fn new(name: &str, command: &str) -> StatusLine {
+        StatusLine {
+            name: name.to_owned(),
+            command: command.to_owned(),
+            clicks: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn clicker(&self) -> StatusLineClicker {
+        StatusLineClicker{clicks: self.clicks.clone()}
+    }
+}
+
+#[derive(Clone)]
+pub struct StatusLineClicker {
+    clicks: Arc<Mutex<Option<ClickPipe>>>,
+}
+
+impl StatusLineClicker {
+    // Clicks are dropped unless the producer asked for them in its header.
+    pub fn click(&self, event: &ClickEvent) -> Result<(), BarError> {
+        let mut clicks = self.clicks.lock().unwrap();
+        if let Some(ref mut pipe) = *clicks {
+            if !pipe.wanted {
+                return Ok(());
+            }
+
+            // clicks go back as another endless array.
+            if pipe.first {
+                writeln!(pipe.stdin, "[")?;
+                writeln!(pipe.stdin, "{}", event.to_json())?;
+                pipe.first = false;
+            } else {
+                writeln!(pipe.stdin, ",{}", event.to_json())?;
+            }
+            pipe.stdin.flush()?;
+        }
+        Ok(())
+    }
+}
+
+impl Sensor for StatusLine {
+    fn run(&self, tx: mpsc::Sender<Message>) -> SensorResult {
+
+        let name = self.name.clone();
+        let command = self.command.clone();
+        let clicks = self.clicks.clone();
+
+        Ok(thread::spawn(move || {
+            let send = |blocks: Vec<Block>| tx.send(Message::StatusLine(name.clone(), blocks))
+                .map_err(|e| BarError::Bar(e.to_string()));
+            let mut plain = false;
+
+            respawn(&format!("statusline '{}'", name), &command, &clicks, |n, line| {
+                if n == 0 {
+                    match json::from_str::<json::Value>(line).ok()
+                        .and_then(|v| Header::from_json(&v).ok()) {
+                            Some(header) => {
+                                plain = false;
+                                if let Some(ref mut pipe) = *clicks.lock().unwrap() {
+                                    pipe.wanted = header.click_events;
+                                }
+                                return Ok(());
+                            }
+                            None => plain = true,
+                        }
+                }
+
+                if plain {
+                    return send(vec![Block::new(line.trim())]);
+                }
+
+                match i3bar::parse_status_line(line) {
+                    Ok(Some(blocks)) => send(blocks),
+                    Ok(None) => Ok(()),
+                    Err(e) => {
+                        println!("statusline '{}' ERROR: {}", name, e);
+                        Ok(())
+                    }
+                }
+            })
+        }))
+    }
+}
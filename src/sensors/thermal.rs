@@ -0,0 +1,290 @@
+use error::BarError;
+use message::Message;
+use sensors::{read_sys_file, Level, Sensor, SensorResult};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+static SYS_PATH: &'static str = "/sys";
+
+// Degrees celsius.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Temperature {
+    pub label: String,
+    pub celsius: f64,
+    pub warning: f64,
+    pub critical: f64,
+    pub level: Level,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fan {
+    pub label: String,
+    pub rpm: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThermalInfo {
+    pub temperatures: Vec<Temperature>,
+    pub fans: Vec<Fan>,
+}
+
+impl ThermalInfo {
+    pub fn new() -> ThermalInfo {
+        ThermalInfo{temperatures: Vec::new(), fans: Vec::new()}
+    }
+
+    pub fn level(&self) -> Level {
+        self.temperatures.iter().map(|t| t.level).max().unwrap_or(Level::Normal)
+    }
+}
+
+// A sensor reading as found in sysfs, before thresholds are applied.
+#[derive(Debug, Clone, PartialEq)]
+struct Reading {
+    label: String,
+    celsius: f64,
+    warning: Option<f64>,
+    critical: Option<f64>,
+}
+
+#[derive(Clone)]
+pub struct Thermal {
+    interval: Duration,
+    root: PathBuf,
+    temperatures: Vec<String>,
+    fans: Vec<String>,
+    warning: f64,
+    critical: f64,
+}
+
+impl Thermal {
+    pub fn new(interval: Duration) -> Thermal {
+        Thermal {
+            interval: interval,
+            root: PathBuf::from(SYS_PATH),
+            temperatures: vec!["Package id 0".to_owned()],
+            fans: Vec::new(),
+            warning: 80.,
+            critical: 95.,
+        }
+    }
+
+    // Labels come from hwmon `temp*_label` files or thermal zone `type`
+    // files, e.g. "Package id 0" or "x86_pkg_temp".
+    pub fn with_temperatures(mut self, labels: Vec<String>) -> Thermal {
+        self.temperatures = labels;
+        self
+    }
+
+    // An empty list reports every fan.
+    pub fn with_fans(mut self, labels: Vec<String>) -> Thermal {
+        self.fans = labels;
+        self
+    }
+
+    // Used when the hardware doesn't report its own limits.
+    pub fn with_thresholds(mut self, warning: f64, critical: f64) -> Thermal {
+        self.warning = warning;
+        self.critical = critical;
+        self
+    }
+
+    pub fn with_root<P: AsRef<Path>>(mut self, root: P) -> Thermal {
+        self.root = root.as_ref().to_path_buf();
+        self
+    }
+
+    pub fn read(&self) -> Result<ThermalInfo, BarError> {
+        let mut readings = read_hwmon_temps(&self.root)?;
+        readings.extend(read_thermal_zones(&self.root));
+
+        let mut temperatures = Vec::new();
+        for label in &self.temperatures {
+            if let Some(r) = readings.iter().find(|r| &r.label == label) {
+                let warning = r.warning.unwrap_or(self.warning);
+                let critical = r.critical.unwrap_or(self.critical);
+                temperatures.push(Temperature {
+                    label: r.label.clone(),
+                    celsius: r.celsius,
+                    warning: warning,
+                    critical: critical,
+                    level: Level::of(r.celsius, warning, critical),
+                });
+            }
+        }
+
+        let fans = read_hwmon_fans(&self.root)?.into_iter()
+            .filter(|f| self.fans.is_empty() || self.fans.contains(&f.label))
+            .collect();
+
+        Ok(ThermalInfo{temperatures: temperatures, fans: fans})
+    }
+}
+
+impl Sensor for Thermal {
+    fn run(&self, tx: mpsc::Sender<Message>) -> SensorResult {
+
+        let iv = self.interval;
+        let sensor = self.clone();
+
+        tx.send(Message::Thermal(sensor.read()?)).unwrap();
+
+        Ok(thread::spawn(move || {
+            loop {
+                thread::park_timeout(iv);
+
+                if let Err(e) = sensor.read().map_err(|e| e.to_string())
+                    .and_then(|info| tx.send(Message::Thermal(info))
+                              .map_err(|e| e.to_string())) {
+                        println!("thermal sensor ERROR: {}", e);
+                    }
+            }
+        }))
+    }
+}
+
+fn read_millidegrees<P: AsRef<Path>>(path: P) -> Option<f64> {
+    read_sys_file(path)
+        .and_then(|s| s.parse::<f64>().ok())
+        .map(|m| m / 1000.)
+}
+
+// hwmon device directories, which are numbered in probe order and so not
+// stable across boots.
+fn hwmon_dirs(root: &Path) -> Result<Vec<PathBuf>, BarError> {
+    let hwmon = root.join("class/hwmon");
+    if !hwmon.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut dirs: Vec<PathBuf> = fs::read_dir(hwmon)?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .collect();
+    dirs.sort();
+    Ok(dirs)
+}
+
+// Indices N of the `<prefix>N_input` files in a hwmon directory.
+fn channels(dir: &Path, prefix: &str) -> Vec<u32> {
+    let mut indices: Vec<u32> = match fs::read_dir(dir) {
+        Ok(entries) => entries.filter_map(|e| e.ok())
+            .filter_map(|e| {
+                let name = e.file_name().to_string_lossy().into_owned();
+                if name.starts_with(prefix) && name.ends_with("_input") {
+                    name[prefix.len()..name.len() - "_input".len()].parse::<u32>().ok()
+                } else {
+                    None
+                }
+            })
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    indices.sort();
+    indices
+}
+
+// Channels without a label file are named after the chip, e.g. "k10temp temp1".
+fn channel_label(dir: &Path, chip: &str, channel: &str) -> String {
+    read_sys_file(dir.join(format!("{}_label", channel)))
+        .unwrap_or(format!("{} {}", chip, channel))
+}
+
+fn read_hwmon_temps(root: &Path) -> Result<Vec<Reading>, BarError> {
+    let mut readings = Vec::new();
+
+    for dir in hwmon_dirs(root)? {
+        let chip = read_sys_file(dir.join("name")).unwrap_or("hwmon".to_owned());
+
+        for i in channels(&dir, "temp") {
+            let channel = format!("temp{}", i);
+            let celsius = match read_millidegrees(dir.join(format!("{}_input", channel))) {
+                Some(c) => c,
+                None => continue,
+            };
+
+            readings.push(Reading {
+                label: channel_label(&dir, &chip, &channel),
+                celsius: celsius,
+                warning: read_millidegrees(dir.join(format!("{}_max", channel))),
+                critical: read_millidegrees(dir.join(format!("{}_crit", channel))),
+            });
+        }
+    }
+
+    Ok(readings)
+}
+
+fn read_hwmon_fans(root: &Path) -> Result<Vec<Fan>, BarError> {
+    let mut fans = Vec::new();
+
+    for dir in hwmon_dirs(root)? {
+        let chip = read_sys_file(dir.join("name")).unwrap_or("hwmon".to_owned());
+
+        for i in channels(&dir, "fan") {
+            let channel = format!("fan{}", i);
+            let rpm = read_sys_file(dir.join(format!("{}_input", channel)))
+                .and_then(|s| s.parse::<u32>().ok());
+
+            if let Some(rpm) = rpm {
+                fans.push(Fan {
+                    label: channel_label(&dir, &chip, &channel),
+                    rpm: rpm,
+                });
+            }
+        }
+    }
+
+    Ok(fans)
+}
+
+// Thermal zones are labelled by their `type` and carry trip points rather
+// than max/crit files.
+fn read_thermal_zones(root: &Path) -> Vec<Reading> {
+    let thermal = root.join("class/thermal");
+    let mut zones: Vec<PathBuf> = match fs::read_dir(thermal) {
+        Ok(entries) => entries.filter_map(|e| e.ok())
+            .filter(|e| e.file_name().to_string_lossy().starts_with("thermal_zone"))
+            .map(|e| e.path())
+            .collect(),
+        Err(_) => return Vec::new(),
+    };
+    zones.sort();
+
+    let mut readings = Vec::new();
+    for zone in zones {
+        let (label, celsius) = match (read_sys_file(zone.join("type")),
+                                      read_millidegrees(zone.join("temp"))) {
+            (Some(l), Some(c)) => (l, c),
+            _ => continue,
+        };
+
+        let mut warning = None;
+        let mut critical = None;
+        for i in 0.. {
+            let kind = match read_sys_file(zone.join(format!("trip_point_{}_type", i))) {
+                Some(kind) => kind,
+                None => break,
+            };
+            let temp = read_millidegrees(zone.join(format!("trip_point_{}_temp", i)));
+
+            match kind.as_ref() {
+                "critical" => critical = temp,
+                "hot" | "passive" if warning.is_none() => warning = temp,
+                _ => (),
+            }
+        }
+
+        readings.push(Reading {
+            label: label,
+            celsius: celsius,
+            warning: warning,
+            critical: critical,
+        });
+    }
+
+    readings
+}
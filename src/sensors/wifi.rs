@@ -3,6 +3,8 @@
 // @license BSD
 //
 use message::Message;
+use netlink::nl80211::{Link, Nl80211};
+use netlink::route::{self, Addresses};
 use sensors::{Sensor, SensorResult};
 use regex::{self, Regex};
 use std::error::Error;
@@ -23,10 +25,20 @@ pub struct ConfigureWifi {
 impl ConfigureWifi {
     pub fn new() -> Result<ConfigureWifi, Box<Error>> {
 
-        // Try to guess the interface. If `iw dev` fails we fail the whole
-        // build as this module is based on this command.
+        // Try to guess the interface, first from nl80211 and then from
+        // `iw dev`. If both fail we fail the whole build.
         //
 
+        let nl_device = Nl80211::connect()
+            .and_then(|mut nl| nl.interfaces())
+            .ok()
+            .and_then(|ifaces| ifaces.into_iter().next())
+            .map(|iface| iface.name);
+
+        if let Some(device) = nl_device {
+            return Ok(ConfigureWifi::with_device(device));
+        }
+
         let output = Command::new("iw").arg("dev")
             .output()?;
 
@@ -41,12 +53,16 @@ impl ConfigureWifi {
             .and_then(|captures| captures.get(1))
             .map_or("wlan0", |c| c.as_str());
 
-        Ok(ConfigureWifi {
+        Ok(ConfigureWifi::with_device(device.to_string()))
+    }
+
+    fn with_device(device: String) -> ConfigureWifi {
+        ConfigureWifi {
             bitrate_degraded: 53.,
             interval: Duration::from_millis(5000),
             ip: false,
-            device: device.to_string(),
-        })
+            device: device,
+        }
     }
 
     pub fn bitrate_degraded(&mut self, bitrate: f64) -> &mut ConfigureWifi {
@@ -84,8 +100,9 @@ impl ConfigureWifi {
 #[derive(Clone, Debug)]
 pub struct WifiStatus {
     pub ssid: Option<String>,
-    pub ip: Option<String>,
+    pub ip: Option<Addresses>,
     pub signal: Option<f64>,
+    pub frequency: Option<u32>,
     pub quality: i64,
     max_bitrate: f64,
 }
@@ -97,6 +114,7 @@ impl WifiStatus {
             ssid: None,
             ip: None,
             signal: None,
+            frequency: None,
             quality: 0,
         }
     }
@@ -120,15 +138,22 @@ impl Sensor for Wifi {
         let ip = self.ip;
         let degraded = self.bitrate_degraded;
 
-        Ok(thread::spawn(move || {
+        let handle = thread::spawn(move || {
 
-            // send a snapshot of current workspace immediately
+            // prefer netlink, falling back to `iw` where it isn't available or fails.
+            let mut nl = Nl80211::connect().ok();
             let mut last_status = WifiStatus::new(degraded);
 
             loop {
 
-                if let Err(e) = get_wifi_status(
-                    dev.clone(), ip, degraded, last_status.clone())
+                let link = match nl.as_mut().map(|nl| nl.link(&dev)) {
+                    Some(Ok(link)) => Ok(link),
+                    Some(Err(_)) | None => iw_link(&dev),
+                };
+
+                if let Err(e) = link
+                    .and_then(|link| get_wifi_status(
+                        link, &dev, ip, degraded, last_status.clone()))
                     .and_then(|status| {
                         last_status = status.clone();
                         tx.send(Message::Wifi(status)).map_err(|e| From::from(e))
@@ -136,21 +161,43 @@ impl Sensor for Wifi {
                         println!("wifistatus ERROR: {}", e); // TODO LOGGING
                     }
 
-                thread::sleep(iv);
+                thread::park_timeout(iv);
+            }
+        });
+
+        // wake the sensor as soon as the link connects or disconnects.
+        let sensor = handle.thread().clone();
+        thread::spawn(move || {
+            let events = match Nl80211::connect().and_then(|nl| nl.events()) {
+                Ok(events) => events,
+                Err(e) => {
+                    println!("wifi netlink events unavailable: {}", e);
+                    return;
+                }
+            };
+
+            loop {
+                match events.wait() {
+                    Ok(_) => sensor.unpark(),
+                    Err(e) => {
+                        println!("wifi netlink events ERROR: {}", e);
+                        return;
+                    }
+                }
             }
-        }))
+        });
+
+        Ok(handle)
     }
 }
 
 
-fn get_wifi_status(device: String,
-                   do_ip: bool,
-                   bitrate_degraded: f64,
-                   last_status: WifiStatus)
-                   -> Result<WifiStatus, Box<Error>> {
+// `iw` output is meant for humans and may change between versions. Only used
+// when nl80211 can't be reached or fails.
+fn iw_link(device: &str) -> Result<Link, Box<Error>> {
 
     let output = Command::new("iw").arg("dev")
-        .arg(&device)
+        .arg(device)
         .arg("link")
         .output()?;
 
@@ -195,23 +242,34 @@ fn get_wifi_status(device: String,
         .and_then(|captures| captures.get(1))
         .map(|m| m.as_str().to_owned());
 
+    let re = Regex::new(r"freq: ([0-9]+)")?;
+    let frequency = re.captures(iw_out)
+        .and_then(|captures| captures.get(1))
+        .and_then(|m| m.as_str().parse::<u32>().ok());
+
+    Ok(Link {
+        ssid: ssid,
+        signal: signal_dbm,
+        bitrate: bitrate.map(|(bits, _)| bits),
+        frequency: frequency,
+    })
+}
+
+fn get_wifi_status(link: Link,
+                   device: &str,
+                   do_ip: bool,
+                   bitrate_degraded: f64,
+                   last_status: WifiStatus)
+                   -> Result<WifiStatus, Box<Error>> {
+
+    let Link{ssid, signal, bitrate, frequency} = link;
+
     let mut ip = None;
     if do_ip {
-        let output = Command::new("ip").arg("addr")
-            .arg("list")
-            .output()?;
-
-        if !output.status.success() {
-            return Err(
-                From::from(
-                    format!("'ip addr list' ERROR: {}",
-                            from_utf8(&output.stderr)?)));
+        let addrs = route::addresses(device)?;
+        if !addrs.is_empty() {
+            ip = Some(addrs);
         }
-
-        let re = Regex::new(r"inet\s+([0-9.]+)")?;
-        ip = re.captures(iw_out)
-            .and_then(|captures| captures.get(1))
-            .map(|m| m.as_str().to_owned());
     }
 
     // reset _max_bitrate if we have changed network
@@ -222,7 +280,7 @@ fn get_wifi_status(device: String,
 
     // If we have a bitrate set new max. Compute quality
     let quality = match bitrate {
-        Some((bits, _)) => {
+        Some(bits) => {
             if bits > max_bitrate {
                 max_bitrate = bits;
             }
@@ -235,7 +293,8 @@ fn get_wifi_status(device: String,
         max_bitrate: max_bitrate,
         ssid: ssid,
         ip: ip,
-        signal: signal_dbm,
+        signal: signal,
+        frequency: frequency,
         quality: quality,
     })
 }
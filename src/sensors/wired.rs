@@ -0,0 +1,167 @@
+use error::BarError;
+use message::Message;
+use netlink::route::{self, Addresses, LinkEvents};
+use sensors::{read_sys_file, Sensor, SensorResult};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+static SYS_PATH: &'static str = "/sys";
+
+static WIRED_PREFIXES: &'static [&'static str] = &["eth", "en"];
+static TUNNEL_PREFIXES: &'static [&'static str] = &["tun", "wg"];
+
+const IFF_UP: u32 = 0x1;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InterfaceKind {
+    Wired,
+    Tunnel,
+}
+
+#[derive(Debug, Clone)]
+pub struct InterfaceStatus {
+    pub name: String,
+    pub kind: InterfaceKind,
+    pub up: bool,
+    // Mbit/s, not reported by tunnels or unplugged cables.
+    pub speed: Option<u32>,
+    pub addresses: Addresses,
+}
+
+#[derive(Clone)]
+pub struct Wired {
+    interval: Duration,
+    root: PathBuf,
+}
+
+impl Wired {
+    pub fn new(interval: Duration) -> Wired {
+        Wired{interval: interval, root: PathBuf::from(SYS_PATH)}
+    }
+
+    pub fn with_root<P: AsRef<Path>>(mut self, root: P) -> Wired {
+        self.root = root.as_ref().to_path_buf();
+        self
+    }
+
+    pub fn read(&self) -> Result<Vec<InterfaceStatus>, BarError> {
+        let addrs = route::all_addresses()?;
+        self.interfaces(&addrs)
+    }
+
+    // Wired and tunnel interfaces under sysfs with their addresses taken
+    // from `addrs`, a dump keyed by interface index. Interfaces removed
+    // while scanning are skipped.
+    pub fn interfaces(&self, addrs: &HashMap<u32, Addresses>)
+                      -> Result<Vec<InterfaceStatus>, BarError> {
+        let net = self.root.join("class/net");
+        let mut ifaces = Vec::new();
+
+        for entry in fs::read_dir(&net)? {
+            let name = entry?.file_name().to_string_lossy().into_owned();
+            let kind = match interface_kind(&name) {
+                Some(kind) => kind,
+                None => continue,
+            };
+
+            let path = net.join(&name);
+            let index = match read_sys_file(path.join("ifindex"))
+                .and_then(|i| i.parse::<u32>().ok()) {
+                    Some(index) => index,
+                    None => continue,
+                };
+
+            let operstate = read_sys_file(path.join("operstate")).unwrap_or("down".to_owned());
+
+            // tunnels have no carrier to report and sit in "unknown" while up.
+            let up = match operstate.as_ref() {
+                "up" => true,
+                "unknown" => read_sys_file(path.join("flags"))
+                    .and_then(|f| u32::from_str_radix(f.trim_left_matches("0x"), 16).ok())
+                    .map_or(false, |flags| flags & IFF_UP != 0),
+                _ => false,
+            };
+
+            // reading speed fails with EINVAL when the link is down.
+            let speed = read_sys_file(path.join("speed"))
+                .and_then(|s| s.parse::<i64>().ok())
+                .and_then(|s| if s > 0 { Some(s as u32) } else { None });
+
+            let addresses = match addrs.get(&index) {
+                Some(a) if up => a.clone(),
+                _ => Addresses::default(),
+            };
+
+            ifaces.push(InterfaceStatus {
+                name: name,
+                kind: kind,
+                up: up,
+                speed: speed,
+                addresses: addresses,
+            });
+        }
+
+        ifaces.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(ifaces)
+    }
+}
+
+impl Sensor for Wired {
+    fn run(&self, tx: mpsc::Sender<Message>) -> SensorResult {
+
+        let iv = self.interval;
+        let sensor = self.clone();
+
+        tx.send(Message::Wired(sensor.read()?)).unwrap();
+
+        let handle = thread::spawn(move || {
+            loop {
+                if let Err(e) = sensor.read().map_err(|e| e.to_string())
+                    .and_then(|ifaces| tx.send(Message::Wired(ifaces))
+                              .map_err(|e| e.to_string())) {
+                        println!("wired sensor ERROR: {}", e);
+                    }
+
+                thread::park_timeout(iv);
+            }
+        });
+
+        // wake the sensor as soon as a link or address changes.
+        let sensor = handle.thread().clone();
+        thread::spawn(move || {
+            let events = match LinkEvents::subscribe() {
+                Ok(events) => events,
+                Err(e) => {
+                    println!("wired link events unavailable: {}", e);
+                    return;
+                }
+            };
+
+            loop {
+                match events.wait() {
+                    Ok(_) => sensor.unpark(),
+                    Err(e) => {
+                        println!("wired link events ERROR: {}", e);
+                        return;
+                    }
+                }
+            }
+        });
+
+        Ok(handle)
+    }
+}
+
+fn interface_kind(name: &str) -> Option<InterfaceKind> {
+    if WIRED_PREFIXES.iter().any(|p| name.starts_with(p)) {
+        Some(InterfaceKind::Wired)
+    } else if TUNNEL_PREFIXES.iter().any(|p| name.starts_with(p)) {
+        Some(InterfaceKind::Tunnel)
+    } else {
+        None
+    }
+}
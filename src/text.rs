@@ -0,0 +1,87 @@
+// Renders widget content as a single line of text for terminals and tmux.
+
+use conrod::color::Rgba;
+use content::Content;
+use error::BarError;
+use std::str::FromStr;
+use super::Orientation;
+
+const SEPARATOR: &'static str = " | ";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Style {
+    Plain,
+    // 24 bit colour escapes, with urgent content in reverse video.
+    Ansi,
+    // tmux's #[fg=...] markup for status-left and status-right.
+    Tmux,
+}
+
+impl FromStr for Style {
+    type Err = BarError;
+
+    fn from_str(s: &str) -> Result<Style, BarError> {
+        match s {
+            "plain" => Ok(Style::Plain),
+            "ansi" => Ok(Style::Ansi),
+            "tmux" => Ok(Style::Tmux),
+            _ => Err(BarError::Bar(format!("unknown text style '{}'", s))),
+        }
+    }
+}
+
+// Left oriented content comes first, as it would on the bar. Empty
+// content is skipped.
+pub fn render(contents: &[Content], style: Style) -> String {
+    let left = contents.iter().filter(|c| c.orientation == Orientation::Left);
+    let right = contents.iter().filter(|c| c.orientation == Orientation::Right);
+
+    left.chain(right)
+        .filter(|c| !c.text.is_empty())
+        .map(|c| render_one(c, style))
+        .collect::<Vec<_>>()
+        .join(SEPARATOR)
+}
+
+fn render_one(content: &Content, style: Style) -> String {
+    let rgb = content.color.map(|c| {
+        let Rgba(r, g, b, _) = c.to_rgb();
+        let byte = |v: f32| (v * 255.).round() as u8;
+        (byte(r), byte(g), byte(b))
+    });
+
+    match style {
+        Style::Plain => content.text.clone(),
+
+        Style::Ansi => {
+            let mut out = String::new();
+            if let Some((r, g, b)) = rgb {
+                out.push_str(&format!("\x1b[38;2;{};{};{}m", r, g, b));
+            }
+            if content.urgent {
+                out.push_str("\x1b[7m");
+            }
+            out.push_str(&content.text);
+            if rgb.is_some() || content.urgent {
+                out.push_str("\x1b[0m");
+            }
+            out
+        }
+
+        Style::Tmux => {
+            let mut out = String::new();
+            if let Some((r, g, b)) = rgb {
+                out.push_str(&format!("#[fg=#{:02x}{:02x}{:02x}]", r, g, b));
+            }
+            if content.urgent {
+                out.push_str("#[reverse]");
+            }
+            // a lone # starts tmux markup.
+            out.push_str(&content.text.replace("#", "##"));
+            if rgb.is_some() || content.urgent {
+                out.push_str("#[default]");
+            }
+            out
+        }
+    }
+}
@@ -0,0 +1,52 @@
+extern crate r3bar;
+
+use r3bar::sensors::backlight::{Backlight, brightness_for};
+use std::time::Duration;
+
+static SYS_ROOT: &'static str = "tests/fixtures/sys";
+
+#[test]
+fn firmware_device_by_default() {
+    let info = Backlight::new(Duration::from_millis(1000)).with_root(SYS_ROOT).read().unwrap();
+
+    assert_eq!(info.device, "acpi_video0");
+    assert_eq!((info.brightness, info.max), (3, 15));
+    assert_eq!(info.percent, 20.);
+}
+
+#[test]
+fn platform_device_preferred_over_raw() {
+    let info = Backlight::new(Duration::from_millis(1000))
+        .with_root("tests/fixtures/backlight")
+        .read().unwrap();
+
+    // amdgpu_bl0 is raw and nv_backlight has no type
+    assert_eq!(info.device, "dell_backlight");
+}
+
+#[test]
+fn chosen_device() {
+    let info = Backlight::new(Duration::from_millis(1000))
+        .with_root(SYS_ROOT)
+        .with_device("intel_backlight")
+        .read().unwrap();
+
+    assert_eq!(info.brightness, 19200);
+    assert_eq!(info.percent, 20.);
+}
+
+#[test]
+fn missing_device() {
+    assert!(Backlight::new(Duration::from_millis(1000))
+            .with_root(SYS_ROOT)
+            .with_device("nope")
+            .read().is_err());
+}
+
+#[test]
+fn brightness_clamped_and_never_off() {
+    assert_eq!(brightness_for(25., 96000), 24000);
+    assert_eq!(brightness_for(150., 96000), 96000);
+    assert_eq!(brightness_for(-5., 96000), 1);
+    assert_eq!(brightness_for(3., 15), 1);
+}
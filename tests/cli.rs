@@ -0,0 +1,75 @@
+// Runs the r3bar and r3msg binaries themselves. Sensors for hardware the
+// test machine doesn't have just report errors, which is what these look
+// for.
+
+extern crate r3bar;
+extern crate serde_json;
+
+use r3bar::i3bar::{self, Header};
+use std::env;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+// Integration tests are built next to the binaries, in deps/ below them.
+fn binary(name: &str) -> Command {
+    let mut dir: PathBuf = env::current_exe().unwrap();
+    dir.pop();
+    if dir.ends_with("deps") {
+        dir.pop();
+    }
+    Command::new(dir.join(name))
+}
+
+fn r3bar() -> Command {
+    binary("r3bar")
+}
+
+#[test]
+fn i3bar_stdout_is_only_protocol() {
+    // the failing command prints an error as soon as it's registered.
+    let output = r3bar()
+        .args(&["--i3bar", "--bench", "2", "--command", "broken:0=exit 3"])
+        .stdin(Stdio::null())
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let mut lines = stdout.lines();
+
+    let header: serde_json::Value = serde_json::from_str(lines.next().unwrap()).unwrap();
+    assert_eq!(Header::from_json(&header).unwrap(), Header{version: 1, click_events: true});
+    assert_eq!(lines.next(), Some("["));
+
+    let status_lines: Vec<_> = lines.map(|l| i3bar::parse_status_line(l).unwrap()).collect();
+    assert!(!status_lines.is_empty());
+    assert!(status_lines.iter().all(|l| l.is_some()));
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("command sensor 'broken' ERROR"));
+}
+
+#[test]
+fn text_once_prints_only_the_status_line() {
+    let output = r3bar()
+        .args(&["--text", "plain", "--once", "--command", "broken:0=exit 3"])
+        .stdin(Stdio::null())
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(stdout.lines().count(), 1);
+    assert!(!stdout.contains("ERROR"));
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("command sensor 'broken' ERROR"));
+}
+
+#[test]
+fn r3msg_screenshot_without_path_prints_usage() {
+    // r3bar isn't running, so trying to send anything would fail.
+    let output = binary("r3msg").arg("screenshot").output().unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(output.status.success());
+    assert!(stdout.starts_with("usage:"));
+}
@@ -0,0 +1,84 @@
+extern crate r3bar;
+
+use r3bar::sensors::command::{parse_output, run_command};
+use std::time::{Duration, Instant};
+
+#[test]
+fn output_lines_follow_i3blocks() {
+    let out = parse_output("full text\nshort\n#FF0000\nignored\n", false);
+
+    assert_eq!(out.full_text, "full text");
+    assert_eq!(out.short_text, Some("short".to_owned()));
+    assert_eq!(out.color, Some("#FF0000".to_owned()));
+    assert!(!out.urgent);
+}
+
+#[test]
+fn output_optional_lines() {
+    let out = parse_output("only this\n", false);
+    assert_eq!(out.short_text, None);
+    assert_eq!(out.color, None);
+
+    // a blank short text still allows a colour
+    let out = parse_output("full\n\n#00FF00\n", false);
+    assert_eq!(out.short_text, None);
+    assert_eq!(out.color, Some("#00FF00".to_owned()));
+}
+
+#[test]
+fn command_runs_in_shell() {
+    let out = run_command("echo hello; echo hi", Duration::from_secs(5)).unwrap();
+
+    assert_eq!(out.full_text, "hello");
+    assert_eq!(out.short_text, Some("hi".to_owned()));
+}
+
+#[test]
+fn exit_33_is_urgent() {
+    let out = run_command("echo fire; exit 33", Duration::from_secs(5)).unwrap();
+
+    assert_eq!(out.full_text, "fire");
+    assert!(out.urgent);
+}
+
+#[test]
+fn failing_command_is_an_error() {
+    assert!(run_command("echo oops >&2; exit 1", Duration::from_secs(5)).is_err());
+}
+
+#[test]
+fn slow_command_is_killed() {
+    let start = Instant::now();
+    assert!(run_command("sleep 10", Duration::from_millis(200)).is_err());
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn persistent_command_reports_lines_and_receives_clicks() {
+    use r3bar::i3bar::ClickEvent;
+    use r3bar::message::Message;
+    use r3bar::sensors::Sensor;
+    use r3bar::sensors::command::PersistentCommand;
+    use std::sync::mpsc;
+
+    // echoes the button of each click it's sent.
+    let script = "echo ready; while read line; do \
+                  echo \"$line\" | sed 's/.*\"button\":\\([0-9]\\).*/clicked \\1/'; done";
+    let command = PersistentCommand::new("clicky", script);
+    let clicker = command.clicker();
+
+    let (tx, rx) = mpsc::channel();
+    command.run(tx).unwrap();
+
+    let next_text = || match rx.recv_timeout(Duration::from_secs(5)).unwrap() {
+        Message::Command(name, out) => {
+            assert_eq!(name, "clicky");
+            out.full_text
+        }
+        m => panic!("unexpected {:?}", m),
+    };
+
+    assert_eq!(next_text(), "ready");
+    clicker.click(&ClickEvent::new("ignored", 3)).unwrap();
+    assert_eq!(next_text(), "clicked 3");
+}
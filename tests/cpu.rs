@@ -0,0 +1,82 @@
+extern crate r3bar;
+
+use r3bar::sensors::cpu::{parse_stat, usage};
+
+static FIRST: &'static str = "\
+cpu  1000 0 500 8000 300 0 0 200 0 0
+cpu0 600 0 200 4000 100 0 0 100 0 0
+cpu1 400 0 300 4000 200 0 0 100 0 0
+intr 12345 0 0
+ctxt 987654
+";
+
+static SECOND: &'static str = "\
+cpu  1200 0 600 8500 400 0 0 300 0 0
+cpu0 800 0 200 4100 100 0 0 100 0 0
+cpu1 400 0 400 4400 300 0 0 200 0 0
+intr 12399 0 0
+ctxt 987999
+";
+
+// cpu1 is taken offline between samples.
+static ONLINE: &'static str = "\
+cpu  1500 0 600 12000 300 0 0 0 0 0
+cpu0 500 0 200 4000 100 0 0 0 0 0
+cpu1 500 0 200 4000 100 0 0 0 0 0
+cpu2 500 0 200 4000 100 0 0 0 0 0
+";
+
+static OFFLINE: &'static str = "\
+cpu  1600 0 600 12100 300 0 0 0 0 0
+cpu0 600 0 200 4000 100 0 0 0 0 0
+cpu2 500 0 200 4100 100 0 0 0 0 0
+";
+
+#[test]
+fn stat_reads_total_and_cores() {
+    let stat = parse_stat(FIRST).unwrap();
+
+    assert_eq!(stat.total.user, 1000);
+    assert_eq!(stat.total.steal, 200);
+    assert_eq!(stat.cores.len(), 2);
+    assert_eq!(stat.cores[&1].iowait, 200);
+}
+
+#[test]
+fn stat_requires_cpu_line() {
+    assert!(parse_stat("intr 1 2 3\n").is_err());
+}
+
+#[test]
+fn usage_between_samples() {
+    let u = usage(&parse_stat(FIRST).unwrap(), &parse_stat(SECOND).unwrap());
+
+    // total: 1000 jiffies elapsed, 300 busy, 100 iowait, 100 steal
+    assert_eq!(u.total.busy, 30.);
+    assert_eq!(u.total.iowait, 10.);
+    assert_eq!(u.total.steal, 10.);
+
+    // cpu0 was busy for 200 of 300 jiffies
+    assert!((u.cores[0].busy - 200. / 3.).abs() < 1e-9);
+    assert_eq!(u.cores[0].iowait, 0.);
+
+    // cpu1: 100 busy, 400 idle, 100 iowait, 100 steal
+    assert!((u.cores[1].busy - 100. / 7.).abs() < 1e-9);
+}
+
+#[test]
+fn stat_keys_cores_by_label() {
+    let stat = parse_stat(OFFLINE).unwrap();
+
+    assert_eq!(stat.cores.keys().collect::<Vec<_>>(), vec![&0, &2]);
+}
+
+#[test]
+fn usage_skips_offlined_cores() {
+    let u = usage(&parse_stat(ONLINE).unwrap(), &parse_stat(OFFLINE).unwrap());
+
+    // cpu0 was fully busy and cpu2 fully idle, cpu1 isn't paired with cpu2
+    assert_eq!(u.cores.len(), 2);
+    assert_eq!(u.cores[0].busy, 100.);
+    assert_eq!(u.cores[1].busy, 0.);
+}
@@ -0,0 +1,55 @@
+extern crate r3bar;
+
+use r3bar::sensors::cpufreq::CpuFreq;
+use std::path::Path;
+use std::time::Duration;
+
+static SYS_ROOT: &'static str = "tests/fixtures/sys";
+
+#[test]
+fn frequencies_in_core_order() {
+    let info = CpuFreq::new(Duration::from_millis(1000)).with_root(SYS_ROOT).read().unwrap();
+
+    assert_eq!(info.frequencies, vec![2400, 800, 3100, 1200]);
+    assert_eq!(info.governor, Some("powersave".to_owned()));
+}
+
+#[test]
+fn non_core_directories_are_skipped() {
+    // cpu/cpufreq and cpu/cpuidle sit beside the cores and start with "cpu" too
+    let cpu = Path::new(SYS_ROOT).join("devices/system/cpu");
+    assert!(cpu.join("cpufreq").is_dir());
+    assert!(cpu.join("cpuidle").is_dir());
+
+    let info = CpuFreq::new(Duration::from_millis(1000)).with_root(SYS_ROOT).read().unwrap();
+    assert_eq!(info.frequencies.len(), 4);
+}
+
+#[test]
+fn platform_profile() {
+    let info = CpuFreq::new(Duration::from_millis(1000)).with_root(SYS_ROOT).read().unwrap();
+
+    assert_eq!(info.profile, Some("balanced".to_owned()));
+    assert_eq!(info.profiles, vec!["quiet", "low-power", "balanced", "performance"]);
+}
+
+#[test]
+fn profiles_cycle_and_wrap() {
+    let mut info = CpuFreq::new(Duration::from_millis(1000)).with_root(SYS_ROOT).read().unwrap();
+    assert_eq!(info.next_profile(), Some("performance".to_owned()));
+
+    info.profile = Some("performance".to_owned());
+    assert_eq!(info.next_profile(), Some("low-power".to_owned()));
+
+    // profiles outside the cycle start it from the beginning
+    info.profile = Some("quiet".to_owned());
+    assert_eq!(info.next_profile(), Some("low-power".to_owned()));
+}
+
+#[test]
+fn no_profiles_without_firmware_support() {
+    let mut info = CpuFreq::new(Duration::from_millis(1000)).with_root(SYS_ROOT).read().unwrap();
+    info.profiles = Vec::new();
+
+    assert_eq!(info.next_profile(), None);
+}
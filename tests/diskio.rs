@@ -0,0 +1,65 @@
+extern crate r3bar;
+
+use r3bar::sensors::diskio::{read_diskstats, rates, DeviceIo};
+use std::time::Duration;
+
+static FIRST: &'static str = "tests/fixtures/diskstats_1";
+static SECOND: &'static str = "tests/fixtures/diskstats_2";
+static BLOCK: &'static str = "tests/fixtures/sys/block";
+
+fn io_of<'a>(io: &'a [DeviceIo], name: &str) -> &'a DeviceIo {
+    io.iter().find(|d| d.name == name).unwrap()
+}
+
+#[test]
+fn diskstats_whole_disks_by_default() {
+    let counters = read_diskstats(FIRST, BLOCK, &[]).unwrap();
+
+    let mut names: Vec<&String> = counters.keys().collect();
+    names.sort();
+    assert_eq!(names, vec!["nvme0n1", "sda"]);
+    assert_eq!(counters["sda"].sectors_read, 288710);
+    assert_eq!(counters["sda"].sectors_written, 267234);
+    assert_eq!(counters["sda"].io_ms, 7020);
+}
+
+#[test]
+fn diskstats_skips_stacked_devices() {
+    // dm-0 sits on nvme0n1p1, which nvme0n1 already counts.
+    assert!(!read_diskstats(FIRST, BLOCK, &[]).unwrap().contains_key("dm-0"));
+
+    let counters = read_diskstats(FIRST, BLOCK, &["dm-0".to_owned()]).unwrap();
+    assert_eq!(counters.keys().collect::<Vec<_>>(), vec!["dm-0"]);
+}
+
+#[test]
+fn diskstats_selects_devices() {
+    let counters = read_diskstats(FIRST, BLOCK, &["sda1".to_owned()]).unwrap();
+    assert_eq!(counters.keys().collect::<Vec<_>>(), vec!["sda1"]);
+}
+
+#[test]
+fn rates_in_bytes_per_second() {
+    let prev = read_diskstats(FIRST, BLOCK, &[]).unwrap();
+    let next = read_diskstats(SECOND, BLOCK, &[]).unwrap();
+    let io = rates(&prev, &next, Duration::from_secs(2));
+
+    let sda = io_of(&io, "sda");
+    assert_eq!((sda.read, sda.write), (524288., 0.));
+
+    let nvme = io_of(&io, "nvme0n1");
+    assert_eq!((nvme.read, nvme.write), (0., 5242880.));
+}
+
+#[test]
+fn busy_percent_of_elapsed() {
+    let prev = read_diskstats(FIRST, BLOCK, &[]).unwrap();
+    let next = read_diskstats(SECOND, BLOCK, &[]).unwrap();
+    let io = rates(&prev, &next, Duration::from_secs(2));
+
+    assert_eq!(io_of(&io, "sda").busy, 25.);
+    // io_ms can run ahead of the wall clock between samples
+    assert_eq!(io_of(&io, "nvme0n1").busy, 100.);
+    // and new devices start at zero
+    assert_eq!(io_of(&io, "sdb").busy, 0.);
+}
@@ -0,0 +1,55 @@
+extern crate r3bar;
+
+use r3bar::sensors::diskusage::parse_mountinfo;
+
+static MOUNTINFO: &'static str = "\
+22 1 259:2 / / rw,relatime shared:1 - ext4 /dev/nvme0n1p2 rw
+23 22 0:21 / /proc rw,nosuid,nodev,noexec,relatime shared:5 - proc proc rw
+24 22 0:22 / /sys rw,nosuid,nodev,noexec,relatime shared:6 - sysfs sysfs rw
+25 22 0:5 / /dev rw,nosuid shared:2 - devtmpfs devtmpfs rw,size=8000000k
+26 25 0:23 / /dev/shm rw,nosuid,nodev shared:3 - tmpfs tmpfs rw
+27 22 259:3 / /home rw,relatime shared:28 - ext4 /dev/nvme0n1p3 rw
+28 22 0:45 / /var/lib/docker/overlay2/abc/merged rw,relatime - overlay overlay rw
+29 27 259:3 /ben/shared /srv/shared rw,relatime shared:28 - ext4 /dev/nvme0n1p3 rw
+30 22 8:17 / /run/media/ben/My\\040Disk rw,nosuid,nodev,relatime shared:300 - vfat /dev/sdb1 rw
+31 22 0:50 / /run/user/1000/gvfs rw,nosuid,nodev,relatime shared:310 - fuse.gvfsd-fuse gvfsd-fuse rw
+33 22 0:51 / /run/user/1000/doc rw,nosuid,nodev,relatime shared:311 - fuse.portal portal rw
+34 27 0:52 / /home/ben/remote rw,nosuid,nodev,relatime shared:312 - fuse.sshfs ben@host:/ rw
+32 24 0:26 / /sys/fs/cgroup rw shared:8 - cgroup2 cgroup2 rw
+";
+
+#[test]
+fn mountinfo_skips_pseudo_filesystems() {
+    let mounts = parse_mountinfo(MOUNTINFO);
+    let fstypes: Vec<&str> = mounts.iter().map(|m| m.fstype.as_ref()).collect();
+
+    assert_eq!(fstypes, vec!["ext4", "ext4", "vfat", "fuse.sshfs"]);
+}
+
+#[test]
+fn mountinfo_skips_virtual_fuse_mounts() {
+    let mounts = parse_mountinfo(MOUNTINFO);
+
+    assert!(!mounts.iter().any(|m| m.mountpoint == "/run/user/1000/gvfs"));
+    assert!(!mounts.iter().any(|m| m.mountpoint == "/run/user/1000/doc"));
+}
+
+#[test]
+fn mountinfo_skips_bind_mounts() {
+    let mounts = parse_mountinfo(MOUNTINFO);
+    assert!(!mounts.iter().any(|m| m.mountpoint == "/srv/shared"));
+}
+
+#[test]
+fn mountinfo_unescapes_mountpoints() {
+    let mounts = parse_mountinfo(MOUNTINFO);
+    let removable = mounts.iter().find(|m| m.source == "/dev/sdb1").unwrap();
+
+    assert_eq!(removable.mountpoint, "/run/media/ben/My Disk");
+}
+
+#[test]
+fn mountinfo_ignores_malformed_lines() {
+    let mounts = parse_mountinfo("garbage\n22 1 259:2 / / rw - ext4\n");
+    assert!(mounts.is_empty());
+}
@@ -0,0 +1,82 @@
+extern crate conrod;
+extern crate r3bar;
+
+use conrod::{color, widget, Sizeable, Widget};
+use r3bar::gauges::Gauge;
+use r3bar::gauges::bars::Bars;
+use r3bar::gauges::blocks::StatusBlock;
+use r3bar::i3bar::{Block, MinWidth};
+
+// No window or GL needed, conrod lays widgets out on its own.
+fn ui() -> conrod::Ui {
+    conrod::UiBuilder::new([400., 26.]).build()
+}
+
+#[test]
+fn measure_before_drawing() {
+    let mut ui = ui();
+    let bars = Bars::new(4, 4., color::WHITE, ui.widget_id_generator());
+    assert_eq!(bars.measure(&ui), None);
+}
+
+#[test]
+fn measure_bars() {
+    let mut ui = ui();
+    let slot_id = ui.widget_id_generator().next();
+    let bars = Bars::new(4, 4., color::WHITE, ui.widget_id_generator());
+
+    {
+        let ui_widgets = &mut ui.set_widgets();
+        widget::Canvas::new().w_h(100., 26.).set(slot_id, ui_widgets);
+        let events = bars.render(vec![50., 25., 10.], slot_id, ui_widgets);
+        assert!(events.is_empty());
+    }
+
+    // three 4px bars 2px apart, the tallest half the slot.
+    assert_eq!(bars.measure(&ui), Some([16., 13.]));
+}
+
+#[test]
+fn ids_cover_every_widget() {
+    let mut ui = ui();
+    let bars = Bars::new(4, 4., color::WHITE, ui.widget_id_generator());
+    let block = StatusBlock::new(color::RED, ui.widget_id_generator());
+
+    assert_eq!(bars.ids().len(), 4);
+    // a cell and its text.
+    assert_eq!(block.ids().len(), 2);
+}
+
+#[test]
+fn status_block_is_at_least_min_width() {
+    let mut ui = ui();
+    let slot_id = ui.widget_id_generator().next();
+    let gauge = StatusBlock::new(color::RED, ui.widget_id_generator());
+
+    // no font is loaded so the text itself has no width.
+    let mut block = Block::new("12:30");
+    block.min_width = Some(MinWidth::Pixels(80));
+
+    {
+        let ui_widgets = &mut ui.set_widgets();
+        widget::Canvas::new().w_h(100., 26.).set(slot_id, ui_widgets);
+        gauge.render(Some(block), slot_id, ui_widgets);
+    }
+
+    assert_eq!(gauge.measure(&ui).map(|d| d[0]), Some(80.));
+}
+
+#[test]
+fn missing_status_block_has_no_width() {
+    let mut ui = ui();
+    let slot_id = ui.widget_id_generator().next();
+    let gauge = StatusBlock::new(color::RED, ui.widget_id_generator());
+
+    {
+        let ui_widgets = &mut ui.set_widgets();
+        widget::Canvas::new().w_h(100., 26.).set(slot_id, ui_widgets);
+        gauge.render(None, slot_id, ui_widgets);
+    }
+
+    assert_eq!(gauge.measure(&ui).map(|d| d[0]), Some(0.));
+}
@@ -0,0 +1,182 @@
+extern crate conrod;
+extern crate r3bar;
+extern crate serde_json;
+
+use conrod::color;
+use r3bar::i3bar::{parse_click_line, parse_color, parse_status_line, Align, Block, ClickEvent,
+                   Header, MinWidth, Writer};
+
+#[test]
+fn click_event_round_trips() {
+    let mut event = ClickEvent::new("volume", 3);
+    event.instance = Some("default".to_owned());
+    event.modifiers = vec!["Shift".to_owned(), "Mod4".to_owned()];
+    event.x = 1320;
+    event.y = 12;
+    event.relative_x = 20;
+    event.relative_y = 12;
+    event.width = 80;
+    event.height = 24;
+
+    assert_eq!(ClickEvent::from_json(&event.to_json()).unwrap(), event);
+}
+
+#[test]
+fn click_event_as_i3bar_sends_it() {
+    let value: serde_json::Value = serde_json::from_str(
+        r#"{"name":"time","button":1,"modifiers":[],"x":1850,"y":8,
+            "relative_x":50,"relative_y":8,"width":90,"height":24}"#).unwrap();
+    let event = ClickEvent::from_json(&value).unwrap();
+
+    assert_eq!(event.name, "time");
+    assert_eq!(event.instance, None);
+    assert_eq!(event.button, 1);
+    assert_eq!((event.x, event.relative_x, event.width), (1850, 50, 90));
+}
+
+#[test]
+fn click_event_needs_button() {
+    let value: serde_json::Value = serde_json::from_str(r#"{"name":"time"}"#).unwrap();
+    assert!(ClickEvent::from_json(&value).is_err());
+}
+
+#[test]
+fn header_with_defaults() {
+    let value: serde_json::Value = serde_json::from_str(r#"{"version":1}"#).unwrap();
+    let header = Header::from_json(&value).unwrap();
+
+    assert_eq!(header, Header::new());
+}
+
+#[test]
+fn header_click_events_ignores_signals() {
+    // producers are never stopped so their signals don't matter.
+    let value: serde_json::Value = serde_json::from_str(
+        r#"{"version":1,"click_events":true,"stop_signal":10,"cont_signal":12}"#).unwrap();
+    let header = Header::from_json(&value).unwrap();
+
+    assert_eq!(header, Header{version: 1, click_events: true});
+}
+
+#[test]
+fn status_line_blocks() {
+    let blocks = parse_status_line(
+        r#"[{"name":"wireless","instance":"wlan0","full_text":"W: up","color":"#00FF00",
+            "min_width":"W: 100%","align":"right","separator":false,"separator_block_width":3},
+           {"full_text":"BAT 4%","urgent":true,"min_width":80,"background":"#FF000080"}],"#)
+        .unwrap().unwrap();
+
+    assert_eq!(blocks.len(), 2);
+
+    let wifi = &blocks[0];
+    assert_eq!(wifi.full_text, "W: up");
+    assert_eq!(wifi.name, Some("wireless".to_owned()));
+    assert_eq!(wifi.instance, Some("wlan0".to_owned()));
+    assert_eq!(wifi.color, Some("#00FF00".to_owned()));
+    assert_eq!(wifi.min_width, Some(MinWidth::Text("W: 100%".to_owned())));
+    assert_eq!(wifi.align, Align::Right);
+    assert!(!wifi.separator);
+    assert_eq!(wifi.separator_block_width, 3);
+
+    let battery = &blocks[1];
+    assert!(battery.urgent);
+    assert_eq!(battery.min_width, Some(MinWidth::Pixels(80)));
+    assert_eq!(battery.align, Align::Left);
+    assert!(battery.separator);
+    assert_eq!(battery.separator_block_width, 9);
+}
+
+#[test]
+fn status_line_leading_comma_and_brackets() {
+    assert_eq!(parse_status_line("[").unwrap(), None);
+    assert_eq!(parse_status_line("").unwrap(), None);
+
+    let blocks = parse_status_line(r#",[{"full_text":"x"}]"#).unwrap().unwrap();
+    assert_eq!(blocks, vec![Block::new("x")]);
+}
+
+#[test]
+fn status_line_block_needs_text() {
+    assert!(parse_status_line(r#"[{"name":"empty"}]"#).is_err());
+    assert!(parse_status_line(r#"{"full_text":"not an array"}"#).is_err());
+}
+
+#[test]
+fn colors() {
+    assert_eq!(parse_color("#FF0000"), Some(color::rgba_bytes(255, 0, 0, 1.)));
+    assert_eq!(parse_color("#00FF0080"), Some(color::rgba_bytes(0, 255, 0, 128. / 255.)));
+    assert_eq!(parse_color("red"), None);
+    assert_eq!(parse_color("#GG0000"), None);
+}
+
+#[test]
+fn color_rejects_non_ascii() {
+    // 7 bytes long, but slicing it by byte pairs splits the é.
+    assert_eq!(parse_color("#a\u{e9}aaa"), None);
+    assert_eq!(parse_color("#\u{e9}\u{e9}\u{e9}"), None);
+    assert_eq!(parse_color("#+F0000"), None);
+}
+
+fn written(header: &Header, lines: &[Vec<Block>]) -> String {
+    let mut writer = Writer::new(Vec::new(), header).unwrap();
+    for blocks in lines {
+        writer.write(blocks).unwrap();
+    }
+    String::from_utf8(writer.into_inner()).unwrap()
+}
+
+#[test]
+fn writer_header_then_array() {
+    let header = Header{click_events: true, ..Header::new()};
+    let out = written(&header, &[vec![Block::new("a")]]);
+    let mut lines = out.lines();
+
+    let value: serde_json::Value = serde_json::from_str(lines.next().unwrap()).unwrap();
+    assert_eq!(Header::from_json(&value).unwrap(), header);
+    assert_eq!(lines.next(), Some("["));
+}
+
+#[test]
+fn writer_output_parses_back() {
+    let mut wifi = Block::new("W: up");
+    wifi.name = Some("wifi".to_owned());
+    wifi.color = Some("#859900".to_owned());
+    wifi.urgent = true;
+    wifi.min_width = Some(MinWidth::Text("W: down".to_owned()));
+    wifi.align = Align::Center;
+    wifi.separator = false;
+
+    let mut time = Block::new("12:00");
+    time.min_width = Some(MinWidth::Pixels(60));
+    time.separator_block_width = 15;
+
+    let status = vec![
+        vec![wifi.clone(), time.clone()],
+        vec![time.clone()],
+    ];
+    let out = written(&Header::new(), &status);
+
+    let parsed: Vec<Vec<Block>> = out.lines().skip(1)
+        .filter_map(|l| parse_status_line(l).unwrap())
+        .collect();
+    assert_eq!(parsed, status);
+}
+
+#[test]
+fn writer_separates_status_lines() {
+    let out = written(&Header::new(), &[vec![Block::new("a")], vec![Block::new("b")]]);
+
+    // a stream that's valid JSON once the array is closed
+    let closed = format!("{}]", out.lines().skip(1).collect::<Vec<_>>().join("\n"));
+    let value: serde_json::Value = serde_json::from_str(&closed).unwrap();
+    assert_eq!(value.as_array().unwrap().len(), 2);
+}
+
+#[test]
+fn click_lines() {
+    assert_eq!(parse_click_line("[").unwrap(), None);
+
+    let event = parse_click_line(r#",{"name":"cpufreq","button":1}"#).unwrap().unwrap();
+    assert_eq!(event.name, "cpufreq");
+    assert_eq!(event.button, 1);
+}
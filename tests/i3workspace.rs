@@ -0,0 +1,59 @@
+extern crate r3bar;
+extern crate unix_socket;
+
+use r3bar::r3ipc::R3Funcs;
+use r3bar::sensors::i3workspace::{get_workspaces, parse_mode, parse_workspaces};
+use std::fs::{self, File};
+use std::io::Read;
+use std::thread;
+use unix_socket::UnixListener;
+
+// A GET_WORKSPACES reply from sway, trimmed. The named workspace "web"
+// has no "num".
+static WORKSPACES: &'static str = "tests/fixtures/sway_workspaces.json";
+
+fn workspaces_reply() -> String {
+    let mut contents = String::new();
+    File::open(WORKSPACES).unwrap().read_to_string(&mut contents).unwrap();
+    contents
+}
+
+#[test]
+fn sway_workspaces_parse() {
+    let workspaces = parse_workspaces(&workspaces_reply()).unwrap();
+    let names: Vec<&str> = workspaces.iter().map(|w| w.name.as_str()).collect();
+
+    assert_eq!(names, vec!["1", "2:mail", "web"]);
+    assert_eq!(workspaces[0].output, "eDP-1");
+    assert_eq!(workspaces[0].rect, (0, 26, 1920, 1054));
+    assert!(workspaces[1].urgent);
+    assert!(workspaces[2].focused);
+    assert_eq!(workspaces[2].num, -1);
+}
+
+#[test]
+fn mode_event_parse() {
+    assert_eq!(parse_mode(r#"{"change":"resize","pango_markup":false}"#).unwrap(), "resize");
+    assert!(parse_mode(r#"{"pango_markup":false}"#).is_err());
+}
+
+#[test]
+fn workspaces_over_socket() {
+    let socket_path = "/tmp/r3bar-test-sway.sock";
+    fs::remove_file(socket_path).ok();
+    let listener = UnixListener::bind(socket_path).unwrap();
+
+    // answers a single GET_WORKSPACES like sway would.
+    let server = thread::spawn(move || {
+        let mut stream = listener.incoming().next().unwrap().unwrap();
+        let (msgtype, _) = stream.receive_i3_message().unwrap();
+        stream.send_i3_message(msgtype, &workspaces_reply()).unwrap();
+        msgtype
+    });
+
+    let workspaces = get_workspaces(socket_path).unwrap();
+
+    assert_eq!(server.join().unwrap(), 1);
+    assert_eq!(workspaces.len(), 3);
+    fs::remove_file(socket_path).ok();
+}
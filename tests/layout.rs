@@ -0,0 +1,231 @@
+extern crate r3bar;
+
+use r3bar::layout::{arrange, hide_separators, place, width, Arrangement, Layout, Orientation,
+                    Rect, Slot, DEFAULT_WIDTH};
+use r3bar::layout::Orientation::{Left, Right};
+
+fn unsmoothed() -> Layout {
+    Layout::new().with_smoothwidth(None)
+}
+
+#[test]
+fn arrange_nothing() {
+    assert_eq!(arrange(&[]), Arrangement{slots: vec![], spacer: 0});
+}
+
+#[test]
+fn arrange_lefts_in_bind_order() {
+    assert_eq!(arrange(&[Left, Left]), Arrangement{
+        slots: vec![Slot::Widget(0), Slot::Separator(0), Slot::Widget(1), Slot::Separator(1)],
+        spacer: 4,
+    });
+}
+
+#[test]
+fn arrange_rights_from_the_right_edge() {
+    assert_eq!(arrange(&[Right, Right]), Arrangement{
+        slots: vec![Slot::Separator(1), Slot::Widget(1), Slot::Separator(0), Slot::Widget(0)],
+        spacer: 0,
+    });
+}
+
+#[test]
+fn arrange_mixed() {
+    // [l1 s l2 s <spacer> s r2 s r1]
+    assert_eq!(arrange(&[Right, Left, Right, Left]), Arrangement{
+        slots: vec![
+            Slot::Widget(1), Slot::Separator(1),
+            Slot::Widget(3), Slot::Separator(3),
+            Slot::Separator(2), Slot::Widget(2),
+            Slot::Separator(0), Slot::Widget(0),
+        ],
+        spacer: 4,
+    });
+}
+
+#[test]
+fn layout_defaults_to_right() {
+    assert_eq!(Layout::new().orientation(), Orientation::Right);
+    assert_eq!(Layout::new().with_orientation(Left).orientation(), Orientation::Left);
+}
+
+#[test]
+fn width_set_ignores_contents() {
+    let layout = unsmoothed().with_width(Some(24));
+    assert_eq!(width(&layout, Some(100), 0), 24);
+    assert_eq!(width(&layout, None, 0), 24);
+}
+
+#[test]
+fn width_from_contents() {
+    assert_eq!(width(&unsmoothed(), Some(87), 0), 87);
+}
+
+#[test]
+fn width_defaults_before_drawing() {
+    assert_eq!(width(&unsmoothed(), None, 0), DEFAULT_WIDTH);
+}
+
+#[test]
+fn width_clamped_to_min_and_max() {
+    let layout = unsmoothed().with_minwidth(Some(50)).with_maxwidth(Some(100));
+    assert_eq!(width(&layout, Some(10), 0), 50);
+    assert_eq!(width(&layout, Some(75), 0), 75);
+    assert_eq!(width(&layout, Some(300), 0), 100);
+    assert_eq!(width(&layout, None, 0), 100);
+}
+
+#[test]
+fn width_min_applies_to_set_width() {
+    let layout = unsmoothed().with_width(Some(10)).with_minwidth(Some(20));
+    assert_eq!(width(&layout, None, 0), 20);
+}
+
+#[test]
+fn width_smoothing_keeps_small_growth() {
+    // the default smooths changes of 4px or less.
+    assert_eq!(width(&Layout::new(), Some(104), 100), 104);
+}
+
+#[test]
+fn width_smoothing_ignores_small_shrinks() {
+    assert_eq!(width(&Layout::new(), Some(96), 100), 100);
+    assert_eq!(width(&Layout::new(), Some(98), 100), 100);
+}
+
+#[test]
+fn width_smoothing_follows_large_changes() {
+    assert_eq!(width(&Layout::new(), Some(95), 100), 95);
+    assert_eq!(width(&Layout::new(), Some(105), 100), 105);
+}
+
+#[test]
+fn width_smoothing_delta_configurable() {
+    let layout = Layout::new().with_smoothwidth(Some(10));
+    assert_eq!(width(&layout, Some(90), 100), 100);
+    assert_eq!(width(&layout, Some(89), 100), 89);
+}
+
+#[test]
+fn width_unsmoothed_follows_contents() {
+    assert_eq!(width(&unsmoothed(), Some(99), 100), 99);
+}
+
+#[test]
+fn width_smoothing_after_clamping() {
+    // clamped to 50 which is within 4 of the previous 52.
+    let layout = Layout::new().with_maxwidth(Some(50));
+    assert_eq!(width(&layout, Some(80), 52), 52);
+}
+
+#[test]
+fn resized_changes_set_width() {
+    let layout = unsmoothed().with_width(Some(100));
+    assert_eq!(width(&layout.resized(20.), None, 0), 120);
+    assert_eq!(width(&layout.resized(-30.5), None, 0), 69);
+}
+
+#[test]
+fn resized_stops_at_zero() {
+    let layout = unsmoothed().with_width(Some(10));
+    assert_eq!(width(&layout.resized(-50.), None, 0), 0);
+}
+
+#[test]
+fn resized_leaves_content_sized_slots() {
+    let layout = unsmoothed().resized(50.);
+    assert_eq!(width(&layout, Some(30), 0), 30);
+}
+
+#[test]
+fn place_spacer_takes_the_rest() {
+    assert_eq!(place(&[10, 20, 30], 1, 100), vec![
+        Rect{x: 0, w: 10},
+        Rect{x: 10, w: 40},
+        Rect{x: 50, w: 20},
+        Rect{x: 70, w: 30},
+    ]);
+}
+
+#[test]
+fn place_spacer_at_either_end() {
+    assert_eq!(place(&[10, 20], 0, 100), vec![
+        Rect{x: 0, w: 70},
+        Rect{x: 70, w: 10},
+        Rect{x: 80, w: 20},
+    ]);
+    assert_eq!(place(&[10, 20], 2, 100), vec![
+        Rect{x: 0, w: 10},
+        Rect{x: 10, w: 20},
+        Rect{x: 30, w: 70},
+    ]);
+}
+
+#[test]
+fn place_nothing_is_all_spacer() {
+    assert_eq!(place(&[], 0, 100), vec![Rect{x: 0, w: 100}]);
+}
+
+#[test]
+fn place_exact_fit() {
+    assert_eq!(place(&[40, 60], 1, 100), vec![
+        Rect{x: 0, w: 40},
+        Rect{x: 40, w: 0},
+        Rect{x: 40, w: 60},
+    ]);
+}
+
+#[test]
+fn place_overflow_runs_off_the_end() {
+    assert_eq!(place(&[80, 60], 1, 100), vec![
+        Rect{x: 0, w: 80},
+        Rect{x: 80, w: 0},
+        Rect{x: 80, w: 60},
+    ]);
+}
+
+#[test]
+fn arrange_and_place_together() {
+    let orientations = [Left, Right];
+    let arrangement = arrange(&orientations);
+    let sep = 24;
+    let widths: Vec<u32> = arrangement.slots.iter().map(|s| match *s {
+        Slot::Widget(0) => 100,
+        Slot::Widget(_) => 50,
+        Slot::Separator(_) => sep,
+    }).collect();
+
+    let rects = place(&widths, arrangement.spacer, 400);
+    assert_eq!(rects, vec![
+        Rect{x: 0, w: 100},
+        Rect{x: 100, w: 24},
+        Rect{x: 124, w: 202},
+        Rect{x: 326, w: 24},
+        Rect{x: 350, w: 50},
+    ]);
+}
+
+#[test]
+fn separators_of_empty_widgets_are_hidden() {
+    let arrangement = arrange(&[Right, Right, Left]);
+    let mut widths: Vec<u32> = arrangement.slots.iter().map(|s| match *s {
+        Slot::Widget(1) => 0,
+        Slot::Widget(_) => 50,
+        Slot::Separator(_) => 24,
+    }).collect();
+
+    hide_separators(&arrangement.slots, &mut widths, &[true, true, true]);
+
+    // [l2 s <spacer> s r1 s r0] with r1 empty
+    assert_eq!(widths, vec![50, 24, 0, 0, 24, 50]);
+}
+
+#[test]
+fn separators_hidden_on_request() {
+    let arrangement = arrange(&[Left, Left]);
+    let mut widths = vec![50, 24, 50, 24];
+
+    hide_separators(&arrangement.slots, &mut widths, &[false, true]);
+
+    assert_eq!(widths, vec![50, 0, 50, 24]);
+}
@@ -0,0 +1,72 @@
+extern crate conrod;
+extern crate r3bar;
+
+use conrod::color;
+use r3bar::Orientation;
+use r3bar::content::Content;
+use r3bar::lemonbar::{parse_action, render};
+
+fn actions(action: &str, text: &str) -> String {
+    format!("%{{A1:1\\:{a}:}}%{{A3:3\\:{a}:}}%{{A4:4\\:{a}:}}%{{A5:5\\:{a}:}}{t}%{{A}}%{{A}}%{{A}}%{{A}}",
+            a = action, t = text)
+}
+
+#[test]
+fn render_splits_by_orientation() {
+    let contents = vec![
+        Content::new("time", "12:30".to_owned()),
+        Content::new("workspace", "1".to_owned()).with_orientation(Orientation::Left),
+        Content::new("ticker", "".to_owned()),
+        Content::new("cpu", "CPU 5%".to_owned()),
+    ];
+
+    assert_eq!(render(&contents),
+               format!("%{{l}}{}%{{r}}{} | {}",
+                       actions("workspace", "1"),
+                       actions("time", "12:30"),
+                       actions("cpu", "CPU 5%%")));
+}
+
+#[test]
+fn render_colors_and_urgency() {
+    let contents = vec![
+        Content::new("mail", "3".to_owned())
+            .with_color(Some(color::rgb(1., 0., 0.)))
+            .with_urgent(true),
+    ];
+
+    assert_eq!(render(&contents),
+               format!("%{{l}}%{{r}}{}", actions("mail", "%{F#FF0000}%{R}3%{R}%{F-}")));
+}
+
+#[test]
+fn render_escapes_instance_colons() {
+    let contents = vec![
+        Content::new("workspace", "2".to_owned()).with_instance(Some("eDP-1:2".to_owned())),
+    ];
+
+    assert!(render(&contents).contains("%{A1:1\\:workspace\\:eDP-1\\:2:}"));
+}
+
+#[test]
+fn parse_action_with_instance() {
+    let event = parse_action("1:workspace:eDP-1:2\n").unwrap().unwrap();
+    assert_eq!(event.name, "workspace");
+    assert_eq!(event.button, 1);
+    assert_eq!(event.instance, Some("eDP-1:2".to_owned()));
+}
+
+#[test]
+fn parse_action_unescapes() {
+    let event = parse_action("4\\:backlight").unwrap().unwrap();
+    assert_eq!(event.name, "backlight");
+    assert_eq!(event.button, 4);
+    assert_eq!(event.instance, None);
+}
+
+#[test]
+fn parse_action_blank_and_bad_lines() {
+    assert_eq!(parse_action("  ").unwrap(), None);
+    assert!(parse_action("time").is_err());
+    assert!(parse_action("left:time").is_err());
+}
@@ -0,0 +1,40 @@
+extern crate r3bar;
+
+use r3bar::sensors::Level;
+use r3bar::sensors::loadavg::{LoadInfo, parse_loadavg, parse_uptime};
+use std::time::Duration;
+
+#[test]
+fn loadavg_averages_and_tasks() {
+    let info = parse_loadavg("0.52 1.58 2.59 3/1127 31337\n").unwrap();
+
+    assert_eq!(info.one, 0.52);
+    assert_eq!(info.five, 1.58);
+    assert_eq!(info.fifteen, 2.59);
+    assert_eq!(info.running, 3);
+    assert_eq!(info.total, 1127);
+}
+
+#[test]
+fn loadavg_rejects_garbage() {
+    assert!(parse_loadavg("").is_err());
+    assert!(parse_loadavg("0.52 0.58 0.59 lots 31337").is_err());
+}
+
+#[test]
+fn uptime_whole_seconds() {
+    assert_eq!(parse_uptime("350735.47 234388.90\n").unwrap(), Duration::from_secs(350735));
+    assert!(parse_uptime("\n").is_err());
+}
+
+#[test]
+fn load_relative_to_cpus() {
+    let mut info = LoadInfo::new();
+    info.one = 6.;
+    info.cpus = 4;
+
+    assert_eq!(info.per_cpu(), 1.5);
+    assert_eq!(Level::of(info.per_cpu(), 1., 2.), Level::Warning);
+    assert_eq!(Level::of(0.5, 1., 2.), Level::Normal);
+    assert_eq!(Level::of(2., 1., 2.), Level::Critical);
+}
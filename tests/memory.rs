@@ -0,0 +1,56 @@
+extern crate r3bar;
+
+use r3bar::sensors::memory::{parse_meminfo, read_zram, Zram};
+
+static MEMINFO: &'static str = "\
+MemTotal:       16000000 kB
+MemFree:         1000000 kB
+MemAvailable:    6000000 kB
+Buffers:          200000 kB
+Cached:          4000000 kB
+SwapCached:        10000 kB
+SReclaimable:     300000 kB
+SwapTotal:       8000000 kB
+SwapFree:        6000000 kB
+HugePages_Total:       0
+";
+
+#[test]
+fn meminfo_in_bytes() {
+    let info = parse_meminfo(MEMINFO).unwrap();
+
+    assert_eq!(info.total, 16000000 * 1024);
+    assert_eq!(info.available, 6000000 * 1024);
+    assert_eq!(info.used, 10000000 * 1024);
+    assert_eq!(info.cached, 4500000 * 1024);
+}
+
+#[test]
+fn meminfo_swap() {
+    let info = parse_meminfo(MEMINFO).unwrap();
+
+    assert_eq!(info.swap_total, 8000000 * 1024);
+    assert_eq!(info.swap_used, 2000000 * 1024);
+}
+
+#[test]
+fn meminfo_requires_available() {
+    assert!(parse_meminfo("MemTotal: 100 kB\n").is_err());
+}
+
+#[test]
+fn zram_summed_over_devices() {
+    let zram = read_zram("tests/fixtures/sys/block").unwrap();
+
+    assert_eq!(zram, Zram {
+        original: 1073741824 + 4194304,
+        used: 285212672 + 1179648,
+        size: 4294967296 + 536870912,
+    });
+}
+
+#[test]
+fn zram_missing_is_none() {
+    assert_eq!(read_zram("tests/fixtures/sys/class"), None);
+    assert_eq!(read_zram("tests/fixtures/nonexistent"), None);
+}
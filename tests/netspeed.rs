@@ -0,0 +1,71 @@
+extern crate r3bar;
+
+use r3bar::sensors::netspeed::{read_net_dev, rates, InterfaceRate};
+use std::time::Duration;
+
+static FIRST: &'static str = "tests/fixtures/net_dev_1";
+static SECOND: &'static str = "tests/fixtures/net_dev_2";
+
+fn rate_of<'a>(speeds: &'a [InterfaceRate], name: &str) -> &'a InterfaceRate {
+    speeds.iter().find(|r| r.name == name).unwrap()
+}
+
+#[test]
+fn net_dev_skips_loopback_by_default() {
+    let counters = read_net_dev(FIRST, &[]).unwrap();
+
+    assert!(!counters.contains_key("lo"));
+    assert_eq!(counters["eth0"].rx_bytes, 1000000);
+    assert_eq!(counters["eth0"].tx_bytes, 500000);
+}
+
+#[test]
+fn net_dev_selects_interfaces() {
+    let counters = read_net_dev(FIRST, &["eth0".to_owned()]).unwrap();
+    assert_eq!(counters.keys().collect::<Vec<_>>(), vec!["eth0"]);
+}
+
+#[test]
+fn rates_are_per_second() {
+    let prev = read_net_dev(FIRST, &[]).unwrap();
+    let next = read_net_dev(SECOND, &[]).unwrap();
+    let speeds = rates(&prev, &next, Duration::from_secs(2));
+
+    let eth0 = rate_of(&speeds, "eth0");
+    assert_eq!(eth0.rx, 1024288.);
+    assert_eq!(eth0.tx, 5120.);
+}
+
+#[test]
+fn rates_handle_32bit_counter_wrap() {
+    let prev = read_net_dev(FIRST, &[]).unwrap();
+    let next = read_net_dev(SECOND, &[]).unwrap();
+    let speeds = rates(&prev, &next, Duration::from_secs(1));
+
+    // 4294967000 -> 704 crosses 2^32
+    assert_eq!(rate_of(&speeds, "wlan0").rx, 1000.);
+}
+
+#[test]
+fn rates_count_from_zero_after_counter_reset() {
+    let prev = read_net_dev(FIRST, &[]).unwrap();
+    let next = read_net_dev(SECOND, &[]).unwrap();
+    let speeds = rates(&prev, &next, Duration::from_secs(1));
+
+    // 10000000 -> 2000 is a recreated interface, not a wrap
+    let usb0 = rate_of(&speeds, "usb0");
+    assert_eq!((usb0.rx, usb0.tx), (2000., 0.));
+}
+
+#[test]
+fn rates_drop_vanished_interfaces() {
+    let prev = read_net_dev(FIRST, &[]).unwrap();
+    let next = read_net_dev(SECOND, &[]).unwrap();
+    let speeds = rates(&prev, &next, Duration::from_secs(1));
+
+    assert!(!speeds.iter().any(|r| r.name == "tun0"));
+
+    // and new ones start at zero
+    let wg0 = rate_of(&speeds, "wg0");
+    assert_eq!((wg0.rx, wg0.tx), (0., 0.));
+}
@@ -0,0 +1,47 @@
+extern crate r3bar;
+
+use r3bar::netlink;
+use r3bar::netlink::nl80211::{parse_interfaces, parse_link, Interface, Link};
+use std::fs::File;
+use std::io::Read;
+
+// Replies for wlan0 (3) associated to "home net" on 5180 MHz, one to
+// NL80211_CMD_GET_INTERFACE and the NL80211_CMD_GET_STATION dump. Netlink
+// is native endian and these were captured on x86_64.
+static INTERFACE: &'static str = "tests/fixtures/nl80211_new_interface";
+static STATION: &'static str = "tests/fixtures/nl80211_new_station";
+
+fn read(path: &str) -> Vec<netlink::Msg> {
+    let mut buf = Vec::new();
+    File::open(path).unwrap().read_to_end(&mut buf).unwrap();
+    netlink::parse(&buf)
+}
+
+#[test]
+fn interfaces_are_named_by_index() {
+    assert_eq!(parse_interfaces(&read(INTERFACE)),
+               vec![Interface{index: 3, name: "wlan0".to_owned()}]);
+}
+
+#[test]
+fn link_from_interface_and_station() {
+    assert_eq!(parse_link(&read(INTERFACE), &read(STATION)), Link {
+        ssid: Some("home net".to_owned()),
+        signal: Some(-52.),
+        bitrate: Some(866.7),
+        frequency: Some(5180),
+    });
+}
+
+#[test]
+fn disconnected_link_is_empty() {
+    let station = read(STATION);
+    let done = &station[station.len() - 1..];
+
+    assert_eq!(parse_link(&[], done), Link {
+        ssid: None,
+        signal: None,
+        bitrate: None,
+        frequency: None,
+    });
+}
@@ -5,7 +5,7 @@ extern crate i3ipc;
 use rand::{thread_rng, Rng};
 
 use r3bar::r3ipc;
-use r3bar::message::Message;
+use r3bar::message::{Message, Screenshot};
 use r3bar::sensors::{Sensor, ipc};
 use std::sync::mpsc;
 use std::{thread};
@@ -17,7 +17,7 @@ fn r3msg() {
     let (tx, rx) = mpsc::channel();
     ipc.run(tx);
 
-    let nmsgs = 1;
+    let nmsgs = 2;
     let main_handle = thread::spawn(move || {
 
         let mut messages = Vec::new();
@@ -39,12 +39,96 @@ fn r3msg() {
     let reply = r3msg.send_msg(r3ipc::UNPARK, "").unwrap();
     assert_reply_success(reply);
 
+    let reply = r3msg.send_msg(r3ipc::UNPARK, "vpn").unwrap();
+    assert_reply_success(reply);
+
     let messages = main_handle.join().unwrap();
-    let msg = &messages[0];
-    match *msg {
-        Message::Unpark => (),
+    match messages[0] {
+        Message::Unpark(None) => (),
         _ => panic!("expected Mesage::Unpark"),
     };
+    match messages[1] {
+        Message::Unpark(Some(ref name)) => assert_eq!(name, "vpn"),
+        _ => panic!("expected Mesage::Unpark for 'vpn'"),
+    };
+}
+
+#[test]
+fn r3msg_screenshot() {
+    let socket_path = &tmp_socket_path();
+    let ipc = ipc::Ipc::new(Some(socket_path)).unwrap();
+    let (tx, rx) = mpsc::channel();
+    ipc.run(tx);
+
+    let shot = Screenshot {
+        path: "/tmp/bar-{output}.png".to_owned(),
+        output: Some("eDP1".to_owned()),
+    };
+
+    // the reply waits for the bar to say how it went.
+    let bar_handle = thread::spawn(move || match rx.recv().unwrap() {
+        Message::Screenshot(received, done) => {
+            done.send(Ok(())).unwrap();
+            received
+        },
+        msg => panic!("expected Message::Screenshot, got {:?}", msg),
+    });
+
+    let mut r3msg = r3ipc::R3Msg::new(Some(socket_path)).unwrap();
+    let reply = r3msg.send_msg(r3ipc::SCREENSHOT, &shot.to_json().to_string()).unwrap();
+    assert_reply_success(reply);
+
+    assert_eq!(bar_handle.join().unwrap(), shot);
+}
+
+#[test]
+fn r3msg_screenshot_reports_failure() {
+    let socket_path = &tmp_socket_path();
+    let ipc = ipc::Ipc::new(Some(socket_path)).unwrap();
+    let (tx, rx) = mpsc::channel();
+    ipc.run(tx);
+
+    let shot = Screenshot {
+        path: "/tmp/bar.png".to_owned(),
+        output: Some("HDMI2".to_owned()),
+    };
+
+    thread::spawn(move || match rx.recv().unwrap() {
+        Message::Screenshot(shot, done) => done.send(shot.outcome(&[None])).unwrap(),
+        msg => panic!("expected Message::Screenshot, got {:?}", msg),
+    });
+
+    let mut r3msg = r3ipc::R3Msg::new(Some(socket_path)).unwrap();
+    let reply = r3msg.send_msg(r3ipc::SCREENSHOT, &shot.to_json().to_string()).unwrap();
+    let outcome = &reply.outcomes[0];
+
+    assert!(!outcome.success);
+    assert_eq!(outcome.error, Some("no such output 'HDMI2'".to_owned()));
+}
+
+#[test]
+fn screenshot_outcome() {
+    let shot = Screenshot {
+        path: "/tmp/bar-{output}.png".to_owned(),
+        output: None,
+    };
+
+    assert_eq!(shot.outcome(&[Some(Ok(())), None]), Ok(()));
+    assert_eq!(shot.outcome(&[Some(Ok(())), Some(Err("disk full".to_owned()))]),
+               Err("disk full".to_owned()));
+    assert_eq!(shot.outcome(&[]), Err("no bar window to capture".to_owned()));
+}
+
+#[test]
+fn r3msg_screenshot_needs_path() {
+    let socket_path = &tmp_socket_path();
+    let ipc = ipc::Ipc::new(Some(socket_path)).unwrap();
+    let (tx, _rx) = mpsc::channel();
+    ipc.run(tx);
+
+    let mut r3msg = r3ipc::R3Msg::new(Some(socket_path)).unwrap();
+    let reply = r3msg.send_msg(r3ipc::SCREENSHOT, r#"{"output": "eDP1"}"#).unwrap();
+    assert!(!reply.outcomes[0].success);
 }
 
 fn assert_reply_success(reply: i3ipc::reply::Command) {
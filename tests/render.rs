@@ -0,0 +1,248 @@
+// Renders bars offscreen and compares them with the PNGs in
+// tests/fixtures/golden. Needs libOSMesa but no X server or GPU, so the
+// rendering tests are ignored by default. Run them with
+//
+//     cargo test --test render -- --ignored
+//
+// After an intended change to how the bar looks run
+//
+//     R3BAR_BLESS=1 cargo test --test render -- --ignored
+//
+// and check the new images in. A missing image fails the test unless
+// R3BAR_BLESS is set, so a new test gets its image the same way.
+
+extern crate conrod;
+extern crate image;
+extern crate r3bar;
+
+use conrod::color;
+use r3bar::bar::{Bar, DispResponse, UiLoop};
+use r3bar::gauges::Gauge;
+use r3bar::gauges::bars::Bars;
+use r3bar::gauges::blocks;
+use r3bar::gauges::button_row::{ButtonRow, ButtonRowInput};
+use r3bar::gauges::icon_text::{IconText, Opts};
+use r3bar::i3bar::Block;
+use r3bar::{Layout, Orientation};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, mpsc};
+use std::time::Duration;
+
+const WIDTH: u32 = 400;
+const HEIGHT: u32 = 26;
+
+// Software GL differs a little between Mesa versions, mostly in how
+// glyph edges are antialiased.
+const CHANNEL_TOLERANCE: i16 = 8;
+const MAX_DIFFERING: f64 = 0.002;
+
+// Widths are measured from the frame before so a bar takes a few frames to
+// settle.
+const SETTLE_FRAMES: usize = 8;
+
+fn font_path() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("assets/fonts/Roboto Mono for Powerline.ttf")
+}
+
+fn golden_path(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/golden").join(format!("{}.png", name))
+}
+
+fn render<F>(setup: F) -> image::RgbaImage
+    where F: Fn(&mut UiLoop) + 'static + Sync + Send
+{
+    let (app_tx, _app_rx) = mpsc::channel::<()>();
+    let (ui_txs, frames) = Bar{}.run_headless(
+        WIDTH, HEIGHT, app_tx, Arc::new(move |ui: &mut UiLoop, _| {
+            ui.set_fonts(&font_path()).unwrap();
+            ui.ui.theme.padding = conrod::position::Padding::none();
+            ui.ui.theme.border_width = 0.;
+            ui.ui.theme.font_size_medium = 14;
+            setup(ui);
+        }));
+
+    let mut last = None;
+    for _ in 0..SETTLE_FRAMES {
+        ui_txs[0].send(DispResponse::WakeDisplay).unwrap();
+        match frames.recv_timeout(Duration::from_millis(1000)) {
+            Ok(frame) => last = Some(frame),
+            // nothing changed so nothing was drawn.
+            Err(_) => break,
+        }
+    }
+
+    last.expect("no frames drawn, is libOSMesa installed?")
+}
+
+// Fraction of pixels with any channel further than the tolerance out.
+fn compare(actual: &image::RgbaImage, expected: &image::RgbaImage) -> Result<f64, String> {
+    if actual.dimensions() != expected.dimensions() {
+        return Err(format!("size {:?}, expected {:?}", actual.dimensions(), expected.dimensions()));
+    }
+
+    let differing = actual.pixels().zip(expected.pixels())
+        .filter(|&(a, e)| a.data.iter().zip(e.data.iter())
+                .any(|(&a, &e)| (a as i16 - e as i16).abs() > CHANNEL_TOLERANCE))
+        .count();
+
+    let (w, h) = actual.dimensions();
+    Ok(differing as f64 / (w * h) as f64)
+}
+
+fn assert_golden(name: &str, actual: &image::RgbaImage) {
+    let path = golden_path(name);
+    let bless = env::var("R3BAR_BLESS").is_ok();
+
+    if bless {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        actual.save(&path).unwrap();
+        return;
+    }
+
+    if !path.exists() {
+        panic!("{} is missing, run with R3BAR_BLESS=1 to create it", path.display());
+    }
+
+    let expected = image::open(&path)
+        .unwrap_or_else(|e| panic!("{}: {}", path.display(), e))
+        .to_rgba();
+
+    let result = compare(actual, &expected);
+    if result.as_ref().map(|&d| d > MAX_DIFFERING).unwrap_or(true) {
+        let failed = path.with_extension("actual.png");
+        actual.save(&failed).unwrap();
+        panic!("{} doesn't match {:?}, see {}", name, result, failed.display());
+    }
+}
+
+#[test]
+fn compare_tolerates_small_differences() {
+    let expected = image::ImageBuffer::from_pixel(10, 10, image::Rgba{data: [100, 100, 100, 255]});
+    let mut actual = expected.clone();
+    actual.put_pixel(0, 0, image::Rgba{data: [108, 92, 100, 255]});
+    assert_eq!(compare(&actual, &expected), Ok(0.));
+
+    actual.put_pixel(1, 0, image::Rgba{data: [109, 100, 100, 255]});
+    assert_eq!(compare(&actual, &expected), Ok(0.01));
+}
+
+#[test]
+fn compare_rejects_other_sizes() {
+    let expected = image::ImageBuffer::from_pixel(10, 10, image::Rgba{data: [0, 0, 0, 255]});
+    let actual = image::ImageBuffer::from_pixel(10, 11, image::Rgba{data: [0, 0, 0, 255]});
+    assert!(compare(&actual, &expected).is_err());
+}
+
+#[test]
+#[ignore]
+fn icon_text() {
+    let frame = render(|ui| {
+        let time = IconText::new(ui.ui.widget_id_generator());
+        ui.bind(Layout::new(), move |slot_id, ui_widgets, update| {
+            time.render(Opts{
+                maybe_icon: None,
+                maybe_text: Some("2017-05-01 12:30".to_owned()),
+                maybe_color: None,
+            }, slot_id, ui_widgets);
+            update
+        });
+    });
+
+    assert_golden("icon_text", &frame);
+}
+
+#[test]
+#[ignore]
+fn left_and_right_layout() {
+    let frame = render(|ui| {
+        let left = IconText::new(ui.ui.widget_id_generator());
+        let right = IconText::new(ui.ui.widget_id_generator());
+        let urgent = IconText::new(ui.ui.widget_id_generator());
+
+        ui.bind(Layout::new().with_orientation(Orientation::Left), move |slot_id, ui_widgets, update| {
+            left.render(Opts{
+                maybe_icon: None,
+                maybe_text: Some("left".to_owned()),
+                maybe_color: None,
+            }, slot_id, ui_widgets);
+            update
+        });
+
+        ui.bind(Layout::new(), move |slot_id, ui_widgets, update| {
+            right.render(Opts{
+                maybe_icon: None,
+                maybe_text: Some("right".to_owned()),
+                maybe_color: None,
+            }, slot_id, ui_widgets);
+            update
+        });
+
+        ui.bind(Layout::new().with_minwidth(Some(80)), move |slot_id, ui_widgets, update| {
+            urgent.render(Opts{
+                maybe_icon: None,
+                maybe_text: Some("95%".to_owned()),
+                maybe_color: Some(color::RED),
+            }, slot_id, ui_widgets);
+            update
+        });
+    });
+
+    assert_golden("left_and_right_layout", &frame);
+}
+
+#[test]
+#[ignore]
+fn bars() {
+    let frame = render(|ui| {
+        let bars = Bars::new(8, 4., color::LIGHT_BLUE, ui.ui.widget_id_generator());
+        ui.bind(Layout::new().with_width(Some(60)), move |slot_id, ui_widgets, update| {
+            bars.render(vec![0., 12.5, 25., 50., 75., 100., 150., -10.], slot_id, ui_widgets);
+            update
+        });
+    });
+
+    assert_golden("bars", &frame);
+}
+
+#[test]
+#[ignore]
+fn button_row() {
+    let frame = render(|ui| {
+        let row = ButtonRow::new(HEIGHT, color::GREY, color::LIGHT_PURPLE, ui.ui.widget_id_generator());
+        ui.bind(Layout::new().with_orientation(Orientation::Left), move |slot_id, ui_widgets, update| {
+            row.render(ButtonRowInput {
+                buttons: vec![
+                    ("1".to_owned(), "1".to_owned(), color::WHITE),
+                    ("2".to_owned(), "2".to_owned(), color::GREY),
+                    ("3".to_owned(), "3".to_owned(), color::RED),
+                ],
+                label: "resize".to_owned(),
+            }, slot_id, ui_widgets);
+            update
+        });
+    });
+
+    assert_golden("button_row", &frame);
+}
+
+#[test]
+#[ignore]
+fn blocks() {
+    let frame = render(|ui| {
+        let mut urgent = Block::new("mail 3");
+        urgent.urgent = true;
+        let mut colored = Block::new("W: 80%");
+        colored.color = Some("#00FF00".to_owned());
+        let mut unseparated = Block::new("E: down");
+        unseparated.separator = false;
+
+        let row = vec![colored, unseparated, urgent, Block::new("12:30")];
+
+        // more slots than blocks, the spare ones take no room.
+        blocks::bind(ui, Layout::new(), 6, color::RED, move || row.clone(), |_| ());
+    });
+
+    assert_golden("blocks", &frame);
+}
@@ -0,0 +1,68 @@
+extern crate r3bar;
+
+use r3bar::netlink;
+use r3bar::netlink::route::{parse_addresses, Addresses};
+use std::fs::File;
+use std::io::Read;
+
+// RTM_GETADDR dump reply from a laptop with lo (1), wlan0 (3), a tunnel (4)
+// with only a link-local address and an OpenVPN tun0 (5). Netlink is native
+// endian and this was captured on x86_64.
+static DUMP: &'static str = "tests/fixtures/rtm_newaddr";
+
+fn dump() -> Vec<netlink::Msg> {
+    let mut buf = Vec::new();
+    File::open(DUMP).unwrap().read_to_end(&mut buf).unwrap();
+    netlink::parse(&buf)
+}
+
+#[test]
+fn dump_splits_into_messages() {
+    let msgs = dump();
+
+    assert_eq!(msgs.len(), 8);
+    assert_eq!(msgs.last().unwrap().kind, 3);
+}
+
+#[test]
+fn addresses_are_keyed_by_interface_index() {
+    let addrs = parse_addresses(&dump());
+    let wlan0 = &addrs[&3];
+
+    assert_eq!(addrs.len(), 4);
+    assert_eq!(wlan0.ipv4, vec!["192.168.1.23".parse().unwrap()]);
+    assert_eq!(wlan0.ipv6, vec!["fe80::1a2b:3cff:fe4d:5e6f".parse().unwrap(),
+                               "2001:db8::23".parse().unwrap()]);
+}
+
+#[test]
+fn addresses_prefer_local_over_peer() {
+    let addrs = parse_addresses(&dump());
+
+    assert_eq!(addrs[&5].ipv4, vec!["10.8.0.2".parse().unwrap()]);
+}
+
+#[test]
+fn primary_prefers_global_addresses() {
+    let addrs = parse_addresses(&dump());
+
+    assert_eq!(addrs[&3].primary(), Some("192.168.1.23".to_owned()));
+    assert_eq!(addrs[&4].primary(), Some("fe80::9".to_owned()));
+
+    let v6_only = Addresses {
+        ipv4: Vec::new(),
+        ipv6: addrs[&3].ipv6.clone(),
+    };
+    assert_eq!(v6_only.primary(), Some("2001:db8::23".to_owned()));
+
+    let link_local_v4 = Addresses {
+        ipv4: vec!["169.254.3.7".parse().unwrap()],
+        ipv6: addrs[&3].ipv6.clone(),
+    };
+    assert_eq!(link_local_v4.primary(), Some("2001:db8::23".to_owned()));
+}
+
+#[test]
+fn primary_of_nothing_is_none() {
+    assert_eq!(Addresses::default().primary(), None);
+}
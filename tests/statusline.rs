@@ -0,0 +1,55 @@
+extern crate r3bar;
+
+use r3bar::i3bar::{Block, ClickEvent};
+use r3bar::message::Message;
+use r3bar::sensors::Sensor;
+use r3bar::sensors::statusline::StatusLine;
+use std::sync::mpsc;
+use std::time::Duration;
+
+fn next_blocks(rx: &mpsc::Receiver<Message>) -> Vec<Block> {
+    match rx.recv_timeout(Duration::from_secs(5)).unwrap() {
+        Message::StatusLine(name, blocks) => {
+            assert_eq!(name, "status");
+            blocks
+        }
+        m => panic!("unexpected {:?}", m),
+    }
+}
+
+#[test]
+fn producer_status_lines() {
+    let script = r#"echo '{"version":1}'; echo '['; echo '[{"full_text":"a"},{"full_text":"b"}],'; echo '[{"full_text":"c"}],'; sleep 5"#;
+    let (tx, rx) = mpsc::channel();
+    StatusLine::new("status", script).run(tx).unwrap();
+
+    assert_eq!(next_blocks(&rx), vec![Block::new("a"), Block::new("b")]);
+    assert_eq!(next_blocks(&rx), vec![Block::new("c")]);
+}
+
+#[test]
+fn plain_text_producer() {
+    let (tx, rx) = mpsc::channel();
+    StatusLine::new("status", "echo one; echo two; sleep 5").run(tx).unwrap();
+
+    assert_eq!(next_blocks(&rx), vec![Block::new("one")]);
+    assert_eq!(next_blocks(&rx), vec![Block::new("two")]);
+}
+
+#[test]
+fn clicks_sent_when_asked_for() {
+    // reports how many lines of the click stream it has read.
+    let script = r#"echo '{"version":1,"click_events":true}'; echo '['; echo '[{"full_text":"ready"}],';
+                    n=0; while read line; do n=$((n+1)); echo "[{\"full_text\":\"$n\"}],"; done"#;
+    let status = StatusLine::new("status", script);
+    let clicker = status.clicker();
+
+    let (tx, rx) = mpsc::channel();
+    status.run(tx).unwrap();
+    assert_eq!(next_blocks(&rx), vec![Block::new("ready")]);
+
+    // the opening bracket and the first event
+    clicker.click(&ClickEvent::new("ready", 1)).unwrap();
+    assert_eq!(next_blocks(&rx), vec![Block::new("1")]);
+    assert_eq!(next_blocks(&rx), vec![Block::new("2")]);
+}
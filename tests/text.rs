@@ -0,0 +1,79 @@
+extern crate conrod;
+extern crate r3bar;
+
+use conrod::color;
+use r3bar::Orientation;
+use r3bar::content::Content;
+use r3bar::i3bar::Block;
+use r3bar::text::{render, Style};
+
+fn contents() -> Vec<Content> {
+    vec![
+        Content::new("time", "12:30".to_owned()),
+        Content::new("disk", "/ 91%".to_owned()).with_color(Some(color::rgb(1., 0., 0.))),
+        Content::new("ticker", "".to_owned()),
+        Content::new("workspaces", "1 2".to_owned()).with_orientation(Orientation::Left),
+    ]
+}
+
+#[test]
+fn plain_puts_left_content_first_and_skips_empty() {
+    assert_eq!(render(&contents(), Style::Plain), "1 2 | 12:30 | / 91%");
+}
+
+#[test]
+fn ansi_colors_with_24_bit_escapes() {
+    assert_eq!(render(&contents(), Style::Ansi),
+               "1 2 | 12:30 | \x1b[38;2;255;0;0m/ 91%\x1b[0m");
+}
+
+#[test]
+fn ansi_reverses_urgent_content() {
+    let urgent = vec![Content::new("mail", "3 new".to_owned()).with_urgent(true)];
+    assert_eq!(render(&urgent, Style::Ansi), "\x1b[7m3 new\x1b[0m");
+}
+
+#[test]
+fn tmux_uses_status_markup() {
+    assert_eq!(render(&contents(), Style::Tmux),
+               "1 2 | 12:30 | #[fg=#ff0000]/ 91%#[default]");
+}
+
+#[test]
+fn tmux_escapes_hashes() {
+    let hashes = vec![Content::new("irc", "#rust".to_owned()).with_urgent(true)];
+    assert_eq!(render(&hashes, Style::Tmux), "#[reverse]##rust#[default]");
+}
+
+#[test]
+fn style_parses_from_option() {
+    assert_eq!("plain".parse::<Style>().unwrap(), Style::Plain);
+    assert_eq!("ansi".parse::<Style>().unwrap(), Style::Ansi);
+    assert_eq!("tmux".parse::<Style>().unwrap(), Style::Tmux);
+    assert!("html".parse::<Style>().is_err());
+}
+
+#[test]
+fn content_to_block_and_back() {
+    let content = Content::new("disk", "/ 91%".to_owned())
+        .with_instance(Some("root".to_owned()))
+        .with_color(Some(color::rgb(1., 0., 0.)))
+        .with_urgent(true);
+
+    let block = content.to_block();
+    assert_eq!(block.full_text, "/ 91%");
+    assert_eq!(block.name, Some("disk".to_owned()));
+    assert_eq!(block.instance, Some("root".to_owned()));
+    assert_eq!(block.color, Some("#FF0000".to_owned()));
+    assert!(block.urgent);
+
+    assert_eq!(Content::from_block(&block), content);
+}
+
+#[test]
+fn content_from_bare_block() {
+    let content = Content::from_block(&Block::new("up 3 days"));
+    assert_eq!(content.name, "");
+    assert_eq!(content.text, "up 3 days");
+    assert_eq!(content.color, None);
+}
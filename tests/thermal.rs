@@ -0,0 +1,65 @@
+extern crate r3bar;
+
+use r3bar::sensors::Level;
+use r3bar::sensors::thermal::Thermal;
+use std::time::Duration;
+
+static SYS_ROOT: &'static str = "tests/fixtures/sys";
+
+fn thermal() -> Thermal {
+    Thermal::new(Duration::from_millis(1000)).with_root(SYS_ROOT)
+}
+
+#[test]
+fn temperature_by_hwmon_label() {
+    let info = thermal().read().unwrap();
+    let package = &info.temperatures[0];
+
+    assert_eq!(info.temperatures.len(), 1);
+    assert_eq!(package.label, "Package id 0");
+    assert_eq!(package.celsius, 84.);
+}
+
+#[test]
+fn thresholds_from_hwmon() {
+    let info = thermal().read().unwrap();
+    let package = &info.temperatures[0];
+
+    assert_eq!(package.warning, 82.);
+    assert_eq!(package.critical, 100.);
+    assert_eq!(package.level, Level::Warning);
+}
+
+#[test]
+fn thresholds_from_thermal_zone_trip_points() {
+    let info = thermal()
+        .with_temperatures(vec!["x86_pkg_temp".to_owned()])
+        .read().unwrap();
+    let zone = &info.temperatures[0];
+
+    assert_eq!(zone.celsius, 58.);
+    assert_eq!(zone.warning, 85.);
+    assert_eq!(zone.critical, 105.);
+    assert_eq!(zone.level, Level::Normal);
+}
+
+#[test]
+fn unlabelled_channels_fall_back_to_chip_name() {
+    let info = thermal()
+        .with_temperatures(vec!["acpitz temp1".to_owned()])
+        .with_thresholds(40., 90.)
+        .read().unwrap();
+
+    assert_eq!(info.temperatures[0].celsius, 45.);
+    assert_eq!(info.temperatures[0].level, Level::Warning);
+}
+
+#[test]
+fn fans_by_label() {
+    let all = thermal().read().unwrap();
+    let rpms: Vec<(&str, u32)> = all.fans.iter().map(|f| (f.label.as_ref(), f.rpm)).collect();
+    assert_eq!(rpms, vec![("thinkpad fan1", 2650), ("GPU fan", 0)]);
+
+    let gpu = thermal().with_fans(vec!["GPU fan".to_owned()]).read().unwrap();
+    assert_eq!(gpu.fans.len(), 1);
+}
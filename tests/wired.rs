@@ -0,0 +1,65 @@
+extern crate r3bar;
+
+use r3bar::netlink;
+use r3bar::netlink::route::{parse_addresses, Addresses};
+use r3bar::sensors::wired::{InterfaceKind, InterfaceStatus, Wired};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::time::Duration;
+
+static SYS_ROOT: &'static str = "tests/fixtures/sys";
+
+// eth0 is 2, wg0 is 4 and tun0 is 5 in the dump, matching their ifindex.
+fn dump() -> HashMap<u32, Addresses> {
+    let mut buf = Vec::new();
+    File::open("tests/fixtures/rtm_newaddr").unwrap().read_to_end(&mut buf).unwrap();
+    parse_addresses(&netlink::parse(&buf))
+}
+
+fn interfaces() -> Vec<InterfaceStatus> {
+    Wired::new(Duration::from_millis(1000))
+        .with_root(SYS_ROOT)
+        .interfaces(&dump())
+        .unwrap()
+}
+
+#[test]
+fn interfaces_skip_wireless_and_loopback() {
+    let names: Vec<String> = interfaces().into_iter().map(|i| i.name).collect();
+
+    assert_eq!(names, vec!["eth0", "eth1", "tun0", "wg0"]);
+}
+
+#[test]
+fn interfaces_skip_half_removed_links() {
+    assert!(!interfaces().iter().any(|i| i.name == "eth9"));
+}
+
+#[test]
+fn wired_link_reports_speed() {
+    let ifaces = interfaces();
+    let eth0 = &ifaces[0];
+
+    assert_eq!(eth0.kind, InterfaceKind::Wired);
+    assert!(eth0.up);
+    assert_eq!(eth0.speed, Some(1000));
+    assert!(eth0.addresses.is_empty());
+
+    assert!(!ifaces[1].up);
+    assert_eq!(ifaces[1].speed, None);
+}
+
+#[test]
+fn tunnel_is_up_by_flags() {
+    let ifaces = interfaces();
+    let tun0 = &ifaces[2];
+    let wg0 = &ifaces[3];
+
+    assert_eq!(tun0.kind, InterfaceKind::Tunnel);
+    assert!(tun0.up);
+    assert_eq!(tun0.addresses.primary(), Some("10.8.0.2".to_owned()));
+
+    assert!(!wg0.up);
+    assert!(wg0.addresses.is_empty());
+}